@@ -0,0 +1,1057 @@
+//! Basketball messages of the Bodet Scorepad protocol, also used by
+//! basketball 3x3, and the team names shared by every sport.
+//!
+//! Decoding is free of side effects: `BodetMessage::try_from(&frame)` turns a
+//! frame into a typed message, and `GameState::apply` is what updates the
+//! state from it.
+//!
+//! Each `MessageNN` struct mirrors the byte layout of message type NN as
+//! documented in `protocol_docs/`. Fields hold the raw ASCII bytes sent by
+//! the scorepad.
+//!
+//! Encoding goes the other way: `BodetMessage::to_frame` builds the frame a
+//! scorepad would send, and `Message18::new`, `Message30::new` and
+//! `Message50::new` fill in the ASCII fields from plain values.
+
+use crate::error::ParseError;
+use crate::frame::{ProtocolFrame, CTRL_DATA, DEFAULT_ADDRESS};
+use crate::sport::Sport;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message18 {
+    pub id_1: u8,            // First byte of message ID
+    pub id_2: u8,            // Second byte of message ID
+    pub status_word: u8,     // Status word byte
+    pub sports_id: u8,       // 5 for basketball, 8 for basketball 3x3
+    pub minutes_1: u8,       // Minutes * 10
+    pub minutes_2: u8,       // Minutes * 1
+    pub seconds_1: u8,       // Seconds * 10
+    pub seconds_2: u8,       // Seconds * 1
+    pub home_time_outs: u8,  // Home time-outs
+    pub guest_time_outs: u8, // Guest time-outs
+    pub byte_11: Option<u8>, // Reserved / unused
+    pub byte_12: Option<u8>, // Reserved / unused
+    pub period: u8,          // Current period
+    pub byte_14: Option<u8>, // Reserved / unused
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message19 {
+    pub id_1: u8,                   // First byte of message ID
+    pub id_2: u8,                   // Second byte of message ID
+    pub sports_id: u8,              // 5 for basketball, 8 for basketball 3x3
+    pub home_time_out_indicator: u8, // Home time-outs, alternates with count - 1 during a time-out
+    pub guest_time_out_indicator: u8, // Guest time-outs, alternates with count - 1 during a time-out
+    pub seconds_1: u8,              // Time-out countdown seconds * 10
+    pub seconds_2: u8,              // Time-out countdown seconds * 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message20 {
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub hours_1: u8,            // Time of day hours * 10
+    pub hours_2: u8,            // Time of day hours * 1
+    pub minutes_1: u8,          // Time of day minutes * 10
+    pub minutes_2: u8,          // Time of day minutes * 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message30 {
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub home_score_1: u8,       // Home score position 1
+    pub home_score_2: u8,       // Home score position 2
+    pub home_score_3: u8,       // Home score position 3
+    pub guest_score_1: u8,      // Guest score position 1
+    pub guest_score_2: u8,      // Guest score position 2
+    pub guest_score_3: u8,      // Guest score position 3
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message31{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub byte_4: Option<u8>,     // Reserved / unused
+    pub home_fouls: u8,         // Home fouls
+    pub byte_6: Option<u8>,     // Reserved / unused
+    pub guest_fouls: u8,        // Guest fouls
+    pub number_player_on_line_1: u8, // Number of player on line position 1
+    pub number_player_on_line_2: u8, // Number of player on line position 2
+    pub number_of_faults_of_player: u8, // Number of faults of player 
+    pub team_of_player: u8,    // Team of player
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message36{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub seconds_1: u8,         // Seconds * 10
+    pub seconds_2: u8,         // Seconds * 1
+    pub seconds_3: u8,         // Seconds * 0.1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message32{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub team_of_player: u8,     // 0x31 home, 0x32 guest, 0x20 initialisation of fouls
+    pub line_of_player_1: u8,   // Line of the player * 10
+    pub line_of_player_2: u8,   // Line of the player * 1
+    pub foul_indicator: u8,     // Personal foul indicator of that line
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message33{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub foul_indicators: Vec<u8>, // Home personal foul indicator per line (1-12)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message34{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub foul_indicators: Vec<u8>, // Guest personal foul indicator per line (1-12)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message56{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub team_of_player: u8,     // 0x31 home, 0x32 guest, 0x20 initialisation
+    pub player_number_1: u8,    // Player number * 10
+    pub player_number_2: u8,    // Player number * 1
+    pub total_score_1: u8,      // Total score * 10
+    pub total_score_2: u8,      // Total score * 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message37{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub player_numbers: Vec<u8>, // Home shirt numbers, 2 characters per line (1-15)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message38{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub player_numbers: Vec<u8>, // Guest shirt numbers, 2 characters per line (1-15)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message50{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub status_word: u8,        // Status word
+    pub seconds_1: u8,         // Seconds * 10
+    pub seconds_2: u8,         // Seconds * 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message60{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // 5 for basketball, 8 for basketball 3x3
+    pub home_status: u8,        // Home team status report, b0 = team foul indicator on
+    pub guest_status: u8,       // Guest team status report, b0 = team foul indicator on
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message98{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub name: Vec<u8>,          // Home team name, 18 characters
+    pub trigram: Vec<u8>,       // Home team trigram, 4 characters
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message99{
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub name: Vec<u8>,          // Guest team name, 18 characters
+    pub trigram: Vec<u8>,       // Guest team trigram, 4 characters
+}
+
+/// Decode a space-padded ASCII text field, returning None when it is blank.
+pub(crate) fn ascii_text(bytes: &[u8]) -> Option<String> {
+    let text: String = bytes.iter().map(|&b| b as char).collect();
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+/// Decode a space-padded ASCII number, returning None when it is blank.
+pub(crate) fn ascii_number(bytes: &[u8]) -> Option<u16> {
+    ascii_text(bytes).and_then(|text| text.parse().ok())
+}
+
+/// Number of personal fouls shown by a foul indicator byte (messages 32-34).
+///
+/// Bit 7 is always set and every lit segment of the indicator is one foul,
+/// so 0x80 is a blank indicator (no fouls).
+pub fn foul_indicator_count(byte: u8) -> u8 {
+    (byte & 0x7F).count_ones() as u8
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatusWord18 {
+    pub clock_type: bool,          // bit 0
+    pub game_clock_off: bool,      // bit 1
+    pub horn_on: bool,             // bit 2
+    pub time_in_tenths: bool,      // bit 4
+    pub new_match: bool,           // bit 6
+    pub b7: bool,                  // bit 7
+}
+
+impl StatusWord18 {
+    pub fn from_byte(byte: u8) -> Self {
+        Self {
+            clock_type: (byte & (1 << 0)) != 0,
+            game_clock_off: (byte & (1 << 1)) != 0,
+            horn_on: (byte & (1 << 2)) != 0,
+            time_in_tenths: (byte & (1 << 4)) != 0,
+            new_match: (byte & (1 << 6)) != 0,
+            b7: (byte & (1 << 7)) != 0,
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        (self.clock_type as u8)
+            | (self.game_clock_off as u8) << 1
+            | (self.horn_on as u8) << 2
+            | (self.time_in_tenths as u8) << 4
+            | (self.new_match as u8) << 6
+            | (self.b7 as u8) << 7
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatusWord50 {
+    pub b0: Option<bool>,                 // bit 0, reserved
+    pub status_possession_timer: bool,    // bit 1, shot clock stopped
+    pub status_possession_horn: bool,     // bit 2, shot clock horn
+    pub status_of_shot_clock: bool,       // bit 3, shot clock blanked
+    pub possession_timer_in_tenths: bool, // bit 4, shot clock in tenths
+    pub b5: Option<bool>,                 // bit 5, reserved
+    pub b6: Option<bool>,                 // bit 6, reserved
+    pub b7: bool,                         // bit 7, always set
+}
+
+impl StatusWord50 {
+    pub fn from_byte(byte: u8) -> Self {
+        Self {
+            b0: None,
+            status_possession_timer: (byte & (1 << 1)) != 0,
+            status_possession_horn: (byte & (1 << 2)) != 0,
+            status_of_shot_clock: (byte & (1 << 3)) != 0,
+            possession_timer_in_tenths: (byte & (1 << 4)) != 0,
+            b5: None,
+            b6: None,
+            b7: (byte & (1 << 7)) != 0,
+        }
+    }
+
+    /// Reserved bits are only set when they are `Some(true)`.
+    pub fn to_byte(&self) -> u8 {
+        (self.b0 == Some(true)) as u8
+            | (self.status_possession_timer as u8) << 1
+            | (self.status_possession_horn as u8) << 2
+            | (self.status_of_shot_clock as u8) << 3
+            | (self.possession_timer_in_tenths as u8) << 4
+            | ((self.b5 == Some(true)) as u8) << 5
+            | ((self.b6 == Some(true)) as u8) << 6
+            | (self.b7 as u8) << 7
+    }
+}
+
+/// ASCII digit of the units of `value`.
+fn ascii_digit(value: u16) -> u8 {
+    b'0' + (value % 10) as u8
+}
+
+impl Message18 {
+    /// Build a message 18 showing the game clock as MM:SS.
+    ///
+    /// Minutes below 10 are sent with a leading blank, like the scorepad
+    /// does. `period` is the raw period byte, `b'1'`..`b'4'` or `b'O'`.
+    pub fn new(
+        status_word: StatusWord18,
+        minutes: u8,
+        seconds: u8,
+        home_time_outs: u8,
+        guest_time_outs: u8,
+        period: u8,
+    ) -> Self {
+        let minutes = minutes as u16;
+        let seconds = seconds as u16;
+        Self {
+            id_1: b'1',
+            id_2: b'8',
+            status_word: status_word.to_byte(),
+            sports_id: SPORTS_ID_BASKETBALL,
+            minutes_1: if minutes < 10 { b' ' } else { ascii_digit(minutes / 10) },
+            minutes_2: ascii_digit(minutes),
+            seconds_1: ascii_digit(seconds / 10),
+            seconds_2: ascii_digit(seconds),
+            home_time_outs: ascii_digit(home_time_outs as u16),
+            guest_time_outs: ascii_digit(guest_time_outs as u16),
+            byte_11: None,
+            byte_12: None,
+            period,
+            byte_14: None,
+        }
+    }
+
+    /// Build a message 18 showing the game clock as SS.t, sent as `SS D t`
+    /// during the last minute. Sets the time in tenths bit of the status word.
+    pub fn with_tenths(
+        status_word: StatusWord18,
+        seconds: u8,
+        tenths: u8,
+        home_time_outs: u8,
+        guest_time_outs: u8,
+        period: u8,
+    ) -> Self {
+        let status_word = StatusWord18 { time_in_tenths: true, ..status_word };
+        let seconds = seconds as u16;
+        Self {
+            minutes_1: ascii_digit(seconds / 10),
+            minutes_2: ascii_digit(seconds),
+            seconds_1: b'D',
+            seconds_2: ascii_digit(tenths as u16),
+            ..Self::new(status_word, 0, 0, home_time_outs, guest_time_outs, period)
+        }
+    }
+}
+
+impl Message30 {
+    /// Build a message 30 from both scores.
+    ///
+    /// The scorepad centres single digits (" 5 ") and aligns two-digit
+    /// scores towards the middle of the board: " 87" for home, "12 " for
+    /// guest.
+    pub fn new(home_score: u16, guest_score: u16) -> Self {
+        let [home_score_1, home_score_2, home_score_3] = match home_score {
+            0..=9 => [b' ', ascii_digit(home_score), b' '],
+            10..=99 => [b' ', ascii_digit(home_score / 10), ascii_digit(home_score)],
+            _ => [ascii_digit(home_score / 100), ascii_digit(home_score / 10), ascii_digit(home_score)],
+        };
+        let [guest_score_1, guest_score_2, guest_score_3] = match guest_score {
+            0..=9 => [b' ', ascii_digit(guest_score), b' '],
+            10..=99 => [ascii_digit(guest_score / 10), ascii_digit(guest_score), b' '],
+            _ => [ascii_digit(guest_score / 100), ascii_digit(guest_score / 10), ascii_digit(guest_score)],
+        };
+        Self {
+            id_1: b'3',
+            id_2: b'0',
+            sports_id: SPORTS_ID_BASKETBALL,
+            home_score_1,
+            home_score_2,
+            home_score_3,
+            guest_score_1,
+            guest_score_2,
+            guest_score_3,
+        }
+    }
+}
+
+impl Message50 {
+    /// Build a message 50 showing the shot clock in whole seconds.
+    pub fn new(status_word: StatusWord50, seconds: u8) -> Self {
+        let seconds = seconds as u16;
+        Self {
+            id_1: b'5',
+            id_2: b'0',
+            status_word: status_word.to_byte(),
+            seconds_1: ascii_digit(seconds / 10),
+            seconds_2: ascii_digit(seconds),
+        }
+    }
+
+    /// Build a message 50 showing the shot clock as S.t. Sets the timer in
+    /// tenths bit of the status word.
+    pub fn with_tenths(status_word: StatusWord50, seconds: u8, tenths: u8) -> Self {
+        let status_word = StatusWord50 { possession_timer_in_tenths: true, ..status_word };
+        Self {
+            seconds_1: ascii_digit(seconds as u16),
+            seconds_2: ascii_digit(tenths as u16),
+            ..Self::new(status_word, 0)
+        }
+    }
+}
+
+/// Sports id byte of basketball messages.
+pub const SPORTS_ID_BASKETBALL: u8 = b'5';
+
+/// Sports id byte of basketball 3x3 messages, which use the basketball layout.
+pub const SPORTS_ID_BASKETBALL_3X3: u8 = b'8';
+
+const BASKETBALL_SPORTS_IDS: &[u8] = &[SPORTS_ID_BASKETBALL, SPORTS_ID_BASKETBALL_3X3];
+
+/// Check that a message carries one of the sports ids of its sport.
+pub(crate) fn check_sports_id(message_type: u8, sports_id: u8, accepted: &[u8]) -> Result<(), ParseError> {
+    if !accepted.contains(&sports_id) {
+        return Err(ParseError::InvalidSportsId { message_type, sports_id });
+    }
+    Ok(())
+}
+
+/// Return an error unless `message` holds the two bytes of its type.
+pub(crate) fn check_type_len(message: &[u8]) -> Result<(), ParseError> {
+    if message.len() < 2 {
+        return Err(ParseError::MessageTooShort {
+            message_type: None,
+            len: message.len(),
+            min_len: 2,
+        });
+    }
+    Ok(())
+}
+
+/// Return an error unless `message` holds at least `min_len` bytes.
+pub(crate) fn check_len(message_type: u8, message: &[u8], min_len: usize) -> Result<(), ParseError> {
+    if message.len() < min_len {
+        return Err(ParseError::MessageTooShort {
+            message_type: Some(message_type),
+            len: message.len(),
+            min_len,
+        });
+    }
+    Ok(())
+}
+
+/// Check that numeric fields hold ASCII digits or blanks.
+pub(crate) fn check_digits(message_type: u8, fields: &[(&'static str, u8)]) -> Result<(), ParseError> {
+    for &(field, byte) in fields {
+        if !(byte.is_ascii_digit() || byte == b' ') {
+            return Err(ParseError::NonDigitField { message_type, field, byte });
+        }
+    }
+    Ok(())
+}
+
+/// A decoded basketball message, one variant per message type.
+///
+/// Decode a frame with `BodetMessage::try_from(&frame)`; apply it to a
+/// GameState with `GameState::apply`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BodetMessage {
+    /// Message 18: game clock, time-outs and period.
+    Message18(Message18),
+    /// Message 19: time-out countdown.
+    Message19(Message19),
+    /// Message 20: time of day.
+    Message20(Message20),
+    /// Message 30: scores.
+    Message30(Message30),
+    /// Message 31: team fouls and the last personal foul call.
+    Message31(Message31),
+    /// Message 32: personal foul indicator of one player line.
+    Message32(Message32),
+    /// Message 33: home personal foul indicators.
+    Message33(Message33),
+    /// Message 34: guest personal foul indicators.
+    Message34(Message34),
+    /// Message 36: game clock in tenths during the last minute.
+    Message36(Message36),
+    /// Message 37: home roster.
+    Message37(Message37),
+    /// Message 38: guest roster.
+    Message38(Message38),
+    /// Message 50: shot clock.
+    Message50(Message50),
+    /// Message 56: points of one player.
+    Message56(Message56),
+    /// Message 60: team status (team foul indicators).
+    Message60(Message60),
+    /// Message 98: home team name.
+    Message98(Message98),
+    /// Message 99: guest team name.
+    Message99(Message99),
+}
+
+impl BodetMessage {
+    /// Basketball or basketball 3x3, from the sports id of the message.
+    /// `None` for messages without a sports id, such as the team names.
+    pub fn sport(&self) -> Option<Sport> {
+        let sports_id = match self {
+            BodetMessage::Message18(m) => m.sports_id,
+            BodetMessage::Message19(m) => m.sports_id,
+            BodetMessage::Message30(m) => m.sports_id,
+            BodetMessage::Message31(m) => m.sports_id,
+            BodetMessage::Message32(m) => m.sports_id,
+            BodetMessage::Message33(m) => m.sports_id,
+            BodetMessage::Message34(m) => m.sports_id,
+            BodetMessage::Message56(m) => m.sports_id,
+            BodetMessage::Message60(m) => m.sports_id,
+            BodetMessage::Message20(_)
+            | BodetMessage::Message36(_)
+            | BodetMessage::Message37(_)
+            | BodetMessage::Message38(_)
+            | BodetMessage::Message50(_)
+            | BodetMessage::Message98(_)
+            | BodetMessage::Message99(_) => return None,
+        };
+        Sport::from_basketball_sports_id(sports_id)
+    }
+
+    /// Serialize the message to the payload of a frame.
+    ///
+    /// Reserved bytes that were not decoded are sent as blanks.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            BodetMessage::Message18(m) => vec![
+                m.id_1,
+                m.id_2,
+                m.status_word,
+                m.sports_id,
+                m.minutes_1,
+                m.minutes_2,
+                m.seconds_1,
+                m.seconds_2,
+                m.home_time_outs,
+                m.guest_time_outs,
+                m.byte_11.unwrap_or(b' '),
+                m.byte_12.unwrap_or(b' '),
+                m.period,
+                m.byte_14.unwrap_or(b' '),
+            ],
+            BodetMessage::Message19(m) => vec![
+                m.id_1,
+                m.id_2,
+                m.sports_id,
+                m.home_time_out_indicator,
+                m.guest_time_out_indicator,
+                m.seconds_1,
+                m.seconds_2,
+            ],
+            BodetMessage::Message20(m) => {
+                vec![m.id_1, m.id_2, m.hours_1, m.hours_2, m.minutes_1, m.minutes_2]
+            }
+            BodetMessage::Message30(m) => vec![
+                m.id_1,
+                m.id_2,
+                m.sports_id,
+                m.home_score_1,
+                m.home_score_2,
+                m.home_score_3,
+                m.guest_score_1,
+                m.guest_score_2,
+                m.guest_score_3,
+            ],
+            BodetMessage::Message31(m) => vec![
+                m.id_1,
+                m.id_2,
+                m.sports_id,
+                m.byte_4.unwrap_or(b' '),
+                m.home_fouls,
+                m.byte_6.unwrap_or(b' '),
+                m.guest_fouls,
+                m.number_player_on_line_1,
+                m.number_player_on_line_2,
+                m.number_of_faults_of_player,
+                m.team_of_player,
+            ],
+            BodetMessage::Message32(m) => vec![
+                m.id_1,
+                m.id_2,
+                m.sports_id,
+                m.team_of_player,
+                m.line_of_player_1,
+                m.line_of_player_2,
+                m.foul_indicator,
+            ],
+            BodetMessage::Message33(m) => {
+                [&[m.id_1, m.id_2, m.sports_id][..], &m.foul_indicators].concat()
+            }
+            BodetMessage::Message34(m) => {
+                [&[m.id_1, m.id_2, m.sports_id][..], &m.foul_indicators].concat()
+            }
+            BodetMessage::Message36(m) => vec![m.id_1, m.id_2, m.seconds_1, m.seconds_2, m.seconds_3],
+            BodetMessage::Message37(m) => [&[m.id_1, m.id_2][..], &m.player_numbers].concat(),
+            BodetMessage::Message38(m) => [&[m.id_1, m.id_2][..], &m.player_numbers].concat(),
+            BodetMessage::Message50(m) => vec![m.id_1, m.id_2, m.status_word, m.seconds_1, m.seconds_2],
+            BodetMessage::Message56(m) => vec![
+                m.id_1,
+                m.id_2,
+                m.sports_id,
+                m.team_of_player,
+                m.player_number_1,
+                m.player_number_2,
+                m.total_score_1,
+                m.total_score_2,
+            ],
+            BodetMessage::Message60(m) => vec![m.id_1, m.id_2, m.sports_id, m.home_status, m.guest_status],
+            BodetMessage::Message98(m) => [&[m.id_1, m.id_2][..], &m.name, &m.trigram].concat(),
+            BodetMessage::Message99(m) => [&[m.id_1, m.id_2][..], &m.name, &m.trigram].concat(),
+        }
+    }
+
+    /// Build the data frame a scorepad would send for this message.
+    pub fn to_frame(&self) -> ProtocolFrame {
+        ProtocolFrame::new(DEFAULT_ADDRESS, CTRL_DATA, self.to_bytes())
+    }
+}
+
+impl TryFrom<&ProtocolFrame> for BodetMessage {
+    type Error = ParseError;
+
+    fn try_from(frame: &ProtocolFrame) -> Result<Self, Self::Error> {
+        let message = &frame.message;
+
+        check_type_len(message)?;
+
+        // First two bytes of the message indicate the message type
+        match (message[0], message[1]) {
+            // Message Type 18
+            (0x31, 0x38) => {
+                check_len(18, message, 14)?;
+
+                let decoded = Message18 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    sports_id: message[3],
+                    minutes_1: message[4],
+                    minutes_2: message[5],
+                    seconds_1: message[6],
+                    seconds_2: message[7],
+                    home_time_outs: message[8],
+                    guest_time_outs: message[9],
+                    byte_11: None,
+                    byte_12: None,
+                    period: message[12],
+                    byte_14: None,
+                };
+
+                check_sports_id(18, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+                check_digits(
+                    18,
+                    &[
+                        ("minutes_1", decoded.minutes_1),
+                        ("minutes_2", decoded.minutes_2),
+                        ("seconds_2", decoded.seconds_2),
+                        ("home_time_outs", decoded.home_time_outs),
+                        ("guest_time_outs", decoded.guest_time_outs),
+                    ],
+                )?;
+                // In the last minute the clock is sent as SS D t
+                if decoded.seconds_1 != b'D' {
+                    check_digits(18, &[("seconds_1", decoded.seconds_1)])?;
+                }
+
+                Ok(BodetMessage::Message18(decoded))
+            }
+
+            // Message Type 19
+            (0x31, 0x39) => {
+                check_len(19, message, 7)?;
+
+                let decoded = Message19 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    home_time_out_indicator: message[3],
+                    guest_time_out_indicator: message[4],
+                    seconds_1: message[5],
+                    seconds_2: message[6],
+                };
+
+                check_sports_id(19, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+                check_digits(
+                    19,
+                    &[
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message19(decoded))
+            }
+
+            // Message Type 20
+            (0x32, 0x30) => {
+                check_len(20, message, 6)?;
+
+                let decoded = Message20 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    hours_1: message[2],
+                    hours_2: message[3],
+                    minutes_1: message[4],
+                    minutes_2: message[5],
+                };
+
+                check_digits(
+                    20,
+                    &[
+                        ("hours_1", decoded.hours_1),
+                        ("hours_2", decoded.hours_2),
+                        ("minutes_1", decoded.minutes_1),
+                        ("minutes_2", decoded.minutes_2),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message20(decoded))
+            }
+
+            // Message Type 30
+            (0x33, 0x30) => {
+                check_len(30, message, 9)?;
+
+                let decoded = Message30 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    home_score_1: message[3],
+                    home_score_2: message[4],
+                    home_score_3: message[5],
+                    guest_score_1: message[6],
+                    guest_score_2: message[7],
+                    guest_score_3: message[8],
+                };
+
+                check_sports_id(30, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+                check_digits(
+                    30,
+                    &[
+                        ("home_score_1", decoded.home_score_1),
+                        ("home_score_2", decoded.home_score_2),
+                        ("home_score_3", decoded.home_score_3),
+                        ("guest_score_1", decoded.guest_score_1),
+                        ("guest_score_2", decoded.guest_score_2),
+                        ("guest_score_3", decoded.guest_score_3),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message30(decoded))
+            }
+
+            // Message Type 31
+            (0x33, 0x31) => {
+                check_len(31, message, 11)?;
+
+                let decoded = Message31 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    byte_4: None,
+                    home_fouls: message[4],
+                    byte_6: None,
+                    guest_fouls: message[6],
+                    number_player_on_line_1: message[7],
+                    number_player_on_line_2: message[8],
+                    number_of_faults_of_player: message[9],
+                    team_of_player: message[10],
+                };
+
+                check_sports_id(31, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+                check_digits(
+                    31,
+                    &[
+                        ("home_fouls", decoded.home_fouls),
+                        ("guest_fouls", decoded.guest_fouls),
+                        ("number_player_on_line_1", decoded.number_player_on_line_1),
+                        ("number_player_on_line_2", decoded.number_player_on_line_2),
+                        ("number_of_faults_of_player", decoded.number_of_faults_of_player),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message31(decoded))
+            }
+
+            // Message Type 50
+            (0x35, 0x30) => {
+                check_len(50, message, 5)?;
+
+                let decoded = Message50 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    seconds_1: message[3],
+                    seconds_2: message[4],
+                };
+
+                check_digits(
+                    50,
+                    &[
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message50(decoded))
+            }
+
+            // Message Type 36
+            (0x33, 0x36) => {
+                check_len(36, message, 5)?;
+
+                let decoded = Message36 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    seconds_1: message[2],
+                    seconds_2: message[3],
+                    seconds_3: message[4],
+                };
+
+                check_digits(
+                    36,
+                    &[
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                        ("seconds_3", decoded.seconds_3),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message36(decoded))
+            }
+
+            // Message Type 32
+            (0x33, 0x32) => {
+                check_len(32, message, 7)?;
+
+                let decoded = Message32 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    team_of_player: message[3],
+                    line_of_player_1: message[4],
+                    line_of_player_2: message[5],
+                    foul_indicator: message[6],
+                };
+
+                check_sports_id(32, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+                check_digits(
+                    32,
+                    &[
+                        ("line_of_player_1", decoded.line_of_player_1),
+                        ("line_of_player_2", decoded.line_of_player_2),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message32(decoded))
+            }
+
+            // Message Type 33
+            (0x33, 0x33) => {
+                check_len(33, message, 15)?;
+
+                let decoded = Message33 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    foul_indicators: message[3..15].to_vec(),
+                };
+
+                check_sports_id(33, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+
+                Ok(BodetMessage::Message33(decoded))
+            }
+
+            // Message Type 34
+            (0x33, 0x34) => {
+                check_len(34, message, 15)?;
+
+                let decoded = Message34 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    foul_indicators: message[3..15].to_vec(),
+                };
+
+                check_sports_id(34, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+
+                Ok(BodetMessage::Message34(decoded))
+            }
+
+            // Message Type 37
+            (0x33, 0x37) => {
+                check_len(37, message, 4)?;
+
+                let decoded = Message37 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    player_numbers: message[2..message.len().min(32)].to_vec(),
+                };
+
+                for &byte in &decoded.player_numbers {
+                    check_digits(37, &[("player_numbers", byte)])?;
+                }
+
+                Ok(BodetMessage::Message37(decoded))
+            }
+
+            // Message Type 38
+            (0x33, 0x38) => {
+                check_len(38, message, 4)?;
+
+                let decoded = Message38 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    player_numbers: message[2..message.len().min(32)].to_vec(),
+                };
+
+                for &byte in &decoded.player_numbers {
+                    check_digits(38, &[("player_numbers", byte)])?;
+                }
+
+                Ok(BodetMessage::Message38(decoded))
+            }
+
+            // Message Type 56
+            (0x35, 0x36) => {
+                check_len(56, message, 8)?;
+
+                let decoded = Message56 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    team_of_player: message[3],
+                    player_number_1: message[4],
+                    player_number_2: message[5],
+                    total_score_1: message[6],
+                    total_score_2: message[7],
+                };
+
+                check_sports_id(56, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+                check_digits(
+                    56,
+                    &[
+                        ("player_number_1", decoded.player_number_1),
+                        ("player_number_2", decoded.player_number_2),
+                        ("total_score_1", decoded.total_score_1),
+                        ("total_score_2", decoded.total_score_2),
+                    ],
+                )?;
+
+                Ok(BodetMessage::Message56(decoded))
+            }
+
+            // Message Type 60
+            (0x36, 0x30) => {
+                check_len(60, message, 5)?;
+
+                let decoded = Message60 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    home_status: message[3],
+                    guest_status: message[4],
+                };
+
+                check_sports_id(60, decoded.sports_id, BASKETBALL_SPORTS_IDS)?;
+
+                Ok(BodetMessage::Message60(decoded))
+            }
+
+            // Message Type 98
+            (0x39, 0x38) => {
+                check_len(98, message, 20)?;
+
+                let decoded = Message98 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    name: message[2..20].to_vec(),
+                    trigram: message[20..message.len().min(24)].to_vec(),
+                };
+
+                Ok(BodetMessage::Message98(decoded))
+            }
+
+            // Message Type 99
+            (0x39, 0x39) => {
+                check_len(99, message, 20)?;
+
+                let decoded = Message99 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    name: message[2..20].to_vec(),
+                    trigram: message[20..message.len().min(24)].to_vec(),
+                };
+
+                Ok(BodetMessage::Message99(decoded))
+            }
+
+            _ => Err(ParseError::UnknownMessageType(message[0], message[1])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::parse_raw_data;
+
+    /// One frame of every message type, as captured in data_log.
+    const CAPTURED: [&[u8]; 16] = [
+        b"\x01\x7F\x02G18\x805 15410  1 \x03%",
+        b"\x01\x7F\x02G1951000\x03%",
+        b"\x01\x7F\x02G201129\x030",
+        b"\x01\x7F\x02G305 3  5 \x03)",
+        b"\x01\x7F\x02G315 0 0    \x03.",
+        b"\x01\x7F\x02G3251 4\x80\x03(",
+        b"\x01\x7F\x02G335\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x03,",
+        b"\x01\x7F\x02G345\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x03+",
+        b"\x01\x7F\x02G36590\x03 ",
+        b"\x01\x7F\x02G37 4 5 6 8 9101314182022232455  \x03!",
+        b"\x01\x7F\x02G38 4 6 7 811141617212728        \x037",
+        b"\x01\x7F\x02G50\x8224\x038",
+        b"\x01\x7F\x02G5651 8 3\x035",
+        b"\x01\x7F\x02G605\x00\x00\x03*",
+        b"\x01\x7F\x02G98CAB MADEIRA           \x03/",
+        b"\x01\x7F\x02G99SPORTIVA              \x03-",
+    ];
+
+    fn decode(bytes: &[u8]) -> BodetMessage {
+        let frame = parse_raw_data(bytes).expect("valid frame");
+        BodetMessage::try_from(&frame).expect("decodable message")
+    }
+
+    #[test]
+    fn captured_messages_encode_to_the_same_bytes() {
+        for captured in CAPTURED {
+            let message = decode(captured);
+            let encoded = message.to_frame().to_bytes();
+            assert_eq!(encoded, captured, "{:?}", message);
+            assert_eq!(decode(&encoded), message);
+        }
+    }
+
+    #[test]
+    fn built_messages_decode_back() {
+        let running = StatusWord18 { b7: true, ..StatusWord18::default() };
+        let shot_clock = StatusWord50 { b7: true, ..StatusWord50::default() };
+        let messages = [
+            BodetMessage::Message18(Message18::new(running, 9, 5, 2, 1, b'3')),
+            BodetMessage::Message18(Message18::with_tenths(running, 42, 7, 0, 0, b'O')),
+            BodetMessage::Message30(Message30::new(7, 103)),
+            BodetMessage::Message30(Message30::new(87, 12)),
+            BodetMessage::Message50(Message50::new(shot_clock, 24)),
+            BodetMessage::Message50(Message50::with_tenths(shot_clock, 4, 2)),
+        ];
+        for message in messages {
+            assert_eq!(decode(&message.to_frame().to_bytes()), message);
+        }
+    }
+
+    #[test]
+    fn lrc_matches_the_scorepad() {
+        // 1:54 in the first quarter, home time-out taken, clock running
+        let running = StatusWord18 { b7: true, ..StatusWord18::default() };
+        let message = BodetMessage::Message18(Message18::new(running, 1, 54, 1, 0, b'1'));
+        assert_eq!(message.to_frame().to_bytes(), CAPTURED[0]);
+        assert_eq!(message.to_frame().lrc, b'%');
+    }
+}
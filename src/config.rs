@@ -0,0 +1,187 @@
+//! Server configuration: listen addresses, directories, log level and the
+//! competition settings of the courts, read from an optional TOML file and
+//! overridden from the command line.
+//!
+//! Every key is optional, e.g.:
+//!
+//! ```toml
+//! tcp_address = "0.0.0.0"
+//! tcp_port = 4001
+//! web_address = "127.0.0.1"
+//! web_port = 3030
+//! static_dir = "/usr/share/scoreboard/static"
+//! log_dir = "/var/log/scoreboard"
+//! log_level = "info"
+//! foul_limit = 5              # personal fouls a player fouls out at, 6 for NBA rules
+//! period_format = "quarters"  # or "halves", e.g. NCAA men
+//! court_routing = "single"    # or "address" or "connection"
+//! ```
+
+use serde::Deserialize;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::court::CourtRouting;
+use crate::error::ConfigError;
+use crate::game_state::DEFAULT_FOUL_LIMIT;
+use crate::period::PeriodFormat;
+
+/// Port the scorepad connects to.
+pub const DEFAULT_TCP_PORT: u16 = 4001;
+
+/// Port of the overlay and the API.
+pub const DEFAULT_WEB_PORT: u16 = 3030;
+
+/// Files the overlay page needs, checked in the static directory at startup.
+const OVERLAY_FILES: [&str; 3] = ["overlay.html", "overlay.css", "overlay.js"];
+
+/// Settings of the server binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub tcp_address: IpAddr, // scorepad ingest
+    pub tcp_port: u16,
+    pub web_address: IpAddr, // overlay and API
+    pub web_port: u16,
+    pub static_dir: PathBuf, // overlay.html, overlay.css, overlay.js and media/
+    pub log_dir: PathBuf,    // session logs and archived games
+    pub log_level: String,   // default level, RUST_LOG overrides it
+    pub foul_limit: u8,      // personal fouls a player fouls out at
+    pub period_format: PeriodFormat,
+    pub court_routing: CourtRouting,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tcp_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            tcp_port: DEFAULT_TCP_PORT,
+            web_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            web_port: DEFAULT_WEB_PORT,
+            static_dir: default_static_dir(),
+            log_dir: PathBuf::from("data_log"),
+            log_level: "info".to_string(),
+            foul_limit: DEFAULT_FOUL_LIMIT,
+            period_format: PeriodFormat::default(),
+            court_routing: CourtRouting::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Read a config file. Keys it doesn't set keep their default, and
+    /// relative paths are taken from the directory of the file.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|error| ConfigError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let file: ConfigFile = toml::from_str(&text).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        let defaults = Config::default();
+        Ok(Config {
+            tcp_address: file.tcp_address.unwrap_or(defaults.tcp_address),
+            tcp_port: file.tcp_port.unwrap_or(defaults.tcp_port),
+            web_address: file.web_address.unwrap_or(defaults.web_address),
+            web_port: file.web_port.unwrap_or(defaults.web_port),
+            static_dir: file.static_dir.map(|dir| base.join(dir)).unwrap_or(defaults.static_dir),
+            log_dir: file.log_dir.map(|dir| base.join(dir)).unwrap_or(defaults.log_dir),
+            log_level: file.log_level.unwrap_or(defaults.log_level),
+            foul_limit: file.foul_limit.unwrap_or(defaults.foul_limit),
+            period_format: file.period_format.unwrap_or(defaults.period_format),
+            court_routing: file.court_routing.unwrap_or(defaults.court_routing),
+        })
+    }
+
+    /// Check the settings that would otherwise only fail once the server
+    /// runs: ports, log level, foul limit and the overlay files.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.tcp_port == 0 {
+            return Err(ConfigError::ZeroPort { setting: "tcp_port" });
+        }
+        if self.web_port == 0 {
+            return Err(ConfigError::ZeroPort { setting: "web_port" });
+        }
+        let overlapping = self.tcp_address == self.web_address
+            || self.tcp_address.is_unspecified()
+            || self.web_address.is_unspecified();
+        if self.tcp_port == self.web_port && overlapping {
+            return Err(ConfigError::SamePort(self.tcp_port));
+        }
+
+        self.level_filter()?;
+        self.validate_game()?;
+
+        for file in OVERLAY_FILES {
+            let path = self.static_dir.join(file);
+            if !path.is_file() {
+                return Err(ConfigError::MissingStaticFile(path));
+            }
+        }
+
+        if self.log_dir.exists() && !self.log_dir.is_dir() {
+            return Err(ConfigError::LogDirNotDirectory(self.log_dir.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Check the competition settings alone, for the tools that keep a game
+    /// state without serving it.
+    pub fn validate_game(&self) -> Result<(), ConfigError> {
+        if self.foul_limit == 0 {
+            return Err(ConfigError::ZeroFoulLimit);
+        }
+        Ok(())
+    }
+
+    /// The log level as a filter.
+    pub fn level_filter(&self) -> Result<log::LevelFilter, ConfigError> {
+        log::LevelFilter::from_str(&self.log_level).map_err(|_| ConfigError::InvalidLogLevel(self.log_level.clone()))
+    }
+
+    pub fn tcp_socket(&self) -> SocketAddr {
+        SocketAddr::new(self.tcp_address, self.tcp_port)
+    }
+
+    pub fn web_socket(&self) -> SocketAddr {
+        SocketAddr::new(self.web_address, self.web_port)
+    }
+}
+
+/// The keys of a config file, so that `load` knows which ones were set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    tcp_address: Option<IpAddr>,
+    tcp_port: Option<u16>,
+    web_address: Option<IpAddr>,
+    web_port: Option<u16>,
+    static_dir: Option<PathBuf>,
+    log_dir: Option<PathBuf>,
+    log_level: Option<String>,
+    foul_limit: Option<u8>,
+    period_format: Option<PeriodFormat>,
+    court_routing: Option<CourtRouting>,
+}
+
+/// `static` in the working directory when there is one, else next to the
+/// executable, else in the source tree the binary was built from.
+fn default_static_dir() -> PathBuf {
+    let candidates = [
+        Some(PathBuf::from("static")),
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join("static"))),
+        Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("static")),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| PathBuf::from("static"))
+}
@@ -0,0 +1,255 @@
+//! Courts: one game state and SSE channel per scoreboard, so one server
+//! process can drive the overlays of several courts.
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::ParseError;
+use crate::frame::{Control, ProtocolFrame, DEFAULT_ADDRESS};
+use crate::game_state::GameState;
+use crate::web_server::SseMessage;
+
+/// Id of the only court when frames are not routed.
+pub const MAIN_COURT: &str = "main";
+
+/// Courts a server drives at most, so that stray address bytes or many
+/// scorepads can't make the list grow without bound.
+pub const MAX_COURTS: usize = 16;
+
+/// Number of messages an SSE client may fall behind before it misses some.
+const SSE_CHANNEL_CAPACITY: usize = 100;
+
+/// How frames are assigned to courts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CourtRouting {
+    /// Every frame feeds the court "main".
+    #[default]
+    Single,
+    /// One court per frame address byte; the id is the address in decimal,
+    /// e.g. "127" for 0x7F.
+    Address,
+    /// One court per scorepad IP address; the id is the address without
+    /// the port, so a scorepad that reconnects keeps its game.
+    Connection,
+}
+
+impl FromStr for CourtRouting {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "single" => Ok(CourtRouting::Single),
+            "address" => Ok(CourtRouting::Address),
+            "connection" => Ok(CourtRouting::Connection),
+            _ => Err(format!(
+                "unknown court routing {:?}, expected \"single\", \"address\" or \"connection\"",
+                value
+            )),
+        }
+    }
+}
+
+/// What a court received besides game messages, to troubleshoot the link
+/// to its scorepad.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Diagnostics {
+    pub frames: u64,                          // valid frames routed to the court
+    pub undecoded_frames: u64,                // valid frames whose message couldn't be decoded
+    pub control_codes: BTreeMap<String, u64>, // frames by CTRL byte other than 'G', e.g. "0x41"
+    pub last_error: Option<String>,           // why the last undecoded frame was refused
+}
+
+impl Diagnostics {
+    /// Count a frame and why it was not applied, if it wasn't. Returns
+    /// true the first time a CTRL byte other than 'G' is seen.
+    pub fn record(&mut self, frame: &ProtocolFrame, error: Option<&ParseError>) -> bool {
+        self.frames += 1;
+        let Some(error) = error else {
+            return false;
+        };
+
+        self.undecoded_frames += 1;
+        self.last_error = Some(error.to_string());
+        match frame.control() {
+            Control::Other(ctrl) => {
+                let count = self.control_codes.entry(format!("0x{:02X}", ctrl)).or_default();
+                *count += 1;
+                *count == 1
+            }
+            Control::Data => false,
+        }
+    }
+}
+
+/// The game state of one court and the channel its SSE clients listen on.
+pub struct Court {
+    pub id: String,
+    pub game_state: Arc<Mutex<GameState>>,
+    pub broadcast_tx: tokio::sync::broadcast::Sender<SseMessage>,
+    pub diagnostics: Mutex<Diagnostics>,
+}
+
+/// Every court of the server. Courts are created on their first frame,
+/// with the settings (foul limit, period format) of a template state, up to
+/// `MAX_COURTS`.
+pub struct Courts {
+    routing: CourtRouting,
+    template: GameState,
+    courts: Mutex<Vec<Arc<Court>>>, // in creation order, the first is the default court
+    full: AtomicBool,               // a court was refused, warned about once
+}
+
+impl Courts {
+    pub fn new(routing: CourtRouting, template: GameState) -> Self {
+        let courts = Self {
+            routing,
+            template,
+            courts: Mutex::new(Vec::new()),
+            full: AtomicBool::new(false),
+        };
+
+        // The default court exists from the start when its id is known, so
+        // overlays can connect before the scorepad does
+        match routing {
+            CourtRouting::Single => {
+                courts.court(MAIN_COURT);
+            }
+            CourtRouting::Address => {
+                courts.court(&DEFAULT_ADDRESS.to_string());
+            }
+            CourtRouting::Connection => {}
+        }
+        courts
+    }
+
+    pub fn routing(&self) -> CourtRouting {
+        self.routing
+    }
+
+    /// Id of the court a frame received from `peer` belongs to.
+    pub fn court_id(&self, frame: &ProtocolFrame, peer: IpAddr) -> String {
+        match self.routing {
+            CourtRouting::Single => MAIN_COURT.to_string(),
+            CourtRouting::Address => frame.address.to_string(),
+            CourtRouting::Connection => peer.to_string(),
+        }
+    }
+
+    /// The court with this id, created if it doesn't exist yet. `None`
+    /// when `MAX_COURTS` courts exist already.
+    pub fn court(&self, id: &str) -> Option<Arc<Court>> {
+        let mut courts = self.courts.lock().unwrap();
+        if let Some(court) = courts.iter().find(|c| c.id == id) {
+            return Some(Arc::clone(court));
+        }
+        if courts.len() >= MAX_COURTS {
+            if !self.full.swap(true, Ordering::Relaxed) {
+                warn!("Already {} courts, ignoring frames for new courts such as {:?}", MAX_COURTS, id);
+            } else {
+                debug!("Ignoring frame for court {:?}, already {} courts", id, MAX_COURTS);
+            }
+            return None;
+        }
+
+        info!("New court {:?}", id);
+        let (broadcast_tx, _) = tokio::sync::broadcast::channel(SSE_CHANNEL_CAPACITY);
+        let court = Arc::new(Court {
+            id: id.to_string(),
+            game_state: Arc::new(Mutex::new(self.template.clone())),
+            broadcast_tx,
+            diagnostics: Mutex::new(Diagnostics::default()),
+        });
+        courts.push(Arc::clone(&court));
+        Some(court)
+    }
+
+    /// The court with this id, if it exists.
+    pub fn get(&self, id: &str) -> Option<Arc<Court>> {
+        self.courts.lock().unwrap().iter().find(|c| c.id == id).cloned()
+    }
+
+    /// The court served by `/api/game` and `/api/stream`: the first one
+    /// created.
+    pub fn default_court(&self) -> Option<Arc<Court>> {
+        self.courts.lock().unwrap().first().cloned()
+    }
+
+    /// Ids of every court, in creation order.
+    pub fn ids(&self) -> Vec<String> {
+        self.courts.lock().unwrap().iter().map(|c| c.id.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::CTRL_DATA;
+    use std::net::Ipv4Addr;
+
+    const PEER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20));
+
+    fn frame(address: u8, ctrl: u8) -> ProtocolFrame {
+        ProtocolFrame::new(address, ctrl, b"305 0  0 ".to_vec())
+    }
+
+    #[test]
+    fn court_ids_by_routing() {
+        let single = Courts::new(CourtRouting::Single, GameState::default());
+        assert_eq!(single.court_id(&frame(0x05, CTRL_DATA), PEER), MAIN_COURT);
+        assert_eq!(single.ids(), [MAIN_COURT]);
+
+        let address = Courts::new(CourtRouting::Address, GameState::default());
+        assert_eq!(address.court_id(&frame(0x05, CTRL_DATA), PEER), "5");
+        assert_eq!(address.court_id(&frame(DEFAULT_ADDRESS, CTRL_DATA), PEER), "127");
+        assert_eq!(address.ids(), ["127"]);
+
+        let connection = Courts::new(CourtRouting::Connection, GameState::default());
+        assert_eq!(connection.court_id(&frame(0x05, CTRL_DATA), PEER), "192.168.1.20");
+        assert!(connection.ids().is_empty());
+    }
+
+    #[test]
+    fn courts_are_created_once() {
+        let courts = Courts::new(CourtRouting::Connection, GameState::default());
+        let first = courts.court("10.0.0.1").unwrap();
+        let again = courts.court("10.0.0.1").unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert!(Arc::ptr_eq(&courts.default_court().unwrap(), &first));
+        assert_eq!(courts.ids(), ["10.0.0.1"]);
+    }
+
+    #[test]
+    fn no_more_than_max_courts() {
+        let courts = Courts::new(CourtRouting::Address, GameState::default());
+        for address in 0..=u8::MAX {
+            courts.court(&address.to_string());
+        }
+        assert_eq!(courts.ids().len(), MAX_COURTS);
+        assert!(courts.court("200").is_none());
+        // Existing courts are still served
+        assert!(courts.court("127").is_some());
+        assert!(courts.get("200").is_none());
+    }
+
+    #[test]
+    fn diagnostics_count_control_codes() {
+        let mut diagnostics = Diagnostics::default();
+        assert!(!diagnostics.record(&frame(DEFAULT_ADDRESS, CTRL_DATA), None));
+
+        let other = frame(DEFAULT_ADDRESS, b'A');
+        let error = ParseError::UnexpectedControl(b'A');
+        assert!(diagnostics.record(&other, Some(&error)));
+        assert!(!diagnostics.record(&other, Some(&error)));
+
+        assert_eq!(diagnostics.frames, 3);
+        assert_eq!(diagnostics.undecoded_frames, 2);
+        assert_eq!(diagnostics.control_codes.get("0x41"), Some(&2));
+        assert_eq!(diagnostics.last_error.as_deref(), Some("Unexpected CTRL byte: 0x41"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Errors raised while decoding frames and messages, reading logs and
+//! loading the server configuration.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// Why a frame or a message could not be decoded.
+///
+/// Frame-level variants come from `parse_raw_data` and `FrameDecoder`,
+/// message-level variants from `BodetMessage::try_from` and
+/// `SportMessage::try_from`. Message types are
+/// given as their protocol number, e.g. 18 for bytes `0x31 0x38`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Fewer bytes than the 6 needed for `SOH address STX CTRL ETX LRC`.
+    FrameTooShort { len: usize },
+    /// No ETX was found within the maximum frame length.
+    FrameTooLong { max_len: usize },
+    /// The next frame started before this one reached its ETX.
+    TruncatedFrame { len: usize },
+    /// The first byte is not SOH (0x01).
+    InvalidSoh(u8),
+    /// The third byte is not STX (0x02).
+    InvalidStx(u8),
+    /// The byte before the LRC is not ETX (0x03).
+    InvalidEtx(u8),
+    /// The transmitted LRC doesn't match the one computed over the frame.
+    LrcMismatch { expected: u8, received: u8 },
+    /// The CTRL byte is not 'G', so the frame doesn't carry a game message.
+    UnexpectedControl(u8),
+    /// The message is too short to hold its type, or all fields of its type.
+    MessageTooShort { message_type: Option<u8>, len: usize, min_len: usize },
+    /// The two type bytes don't name a known message.
+    UnknownMessageType(u8, u8),
+    /// The sports id byte doesn't belong to the sport of the message type.
+    InvalidSportsId { message_type: u8, sports_id: u8 },
+    /// A numeric field holds something other than an ASCII digit or a blank.
+    NonDigitField { message_type: u8, field: &'static str, byte: u8 },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::FrameTooShort { len } => write!(f, "Frame too short: {} bytes", len),
+            ParseError::FrameTooLong { max_len } => write!(f, "No ETX within {} bytes", max_len),
+            ParseError::TruncatedFrame { len } => write!(f, "Frame cut off by the next SOH after {} bytes", len),
+            ParseError::InvalidSoh(byte) => write!(f, "Invalid SOH: 0x{:02X}", byte),
+            ParseError::InvalidStx(byte) => write!(f, "Invalid STX: 0x{:02X}", byte),
+            ParseError::InvalidEtx(byte) => write!(f, "Invalid ETX: 0x{:02X}", byte),
+            ParseError::LrcMismatch { expected, received } => write!(
+                f,
+                "LRC mismatch: expected 0x{:02X}, received 0x{:02X}",
+                expected, received
+            ),
+            ParseError::UnexpectedControl(byte) => write!(f, "Unexpected CTRL byte: 0x{:02X}", byte),
+            ParseError::MessageTooShort { message_type: Some(message_type), len, min_len } => write!(
+                f,
+                "Message Type {} too short: {} bytes, need {}",
+                message_type, len, min_len
+            ),
+            ParseError::MessageTooShort { message_type: None, len, min_len } => write!(
+                f,
+                "Message too short to determine type: {} bytes, need {}",
+                len, min_len
+            ),
+            ParseError::UnknownMessageType(id_1, id_2) => {
+                write!(f, "Unknown message type: 0x{:02X} 0x{:02X}", id_1, id_2)
+            }
+            ParseError::InvalidSportsId { message_type, sports_id } => write!(
+                f,
+                "Message Type {}: invalid sports id 0x{:02X}",
+                message_type, sports_id
+            ),
+            ParseError::NonDigitField { message_type, field, byte } => write!(
+                f,
+                "Message Type {}: non-digit byte 0x{:02X} in {}",
+                message_type, byte, field
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why the server configuration is not usable. Raised by `Config::load`
+/// and `Config::validate` before anything is bound or opened.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file could not be read.
+    Read { path: PathBuf, error: std::io::Error },
+    /// The config file is not valid TOML, or holds an unknown key or a
+    /// value of the wrong type.
+    Parse { path: PathBuf, message: String },
+    /// A port is 0, so clients couldn't know where to connect.
+    ZeroPort { setting: &'static str },
+    /// The scorepad and web servers would listen on the same port.
+    SamePort(u16),
+    /// Players would foul out before their first foul.
+    ZeroFoulLimit,
+    /// The log level is not one of off, error, warn, info, debug or trace.
+    InvalidLogLevel(String),
+    /// The static directory doesn't exist or lacks a file of the overlay.
+    MissingStaticFile(PathBuf),
+    /// The log directory path exists but is not a directory.
+    LogDirNotDirectory(PathBuf),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, error } => write!(f, "Cannot read config file {}: {}", path.display(), error),
+            ConfigError::Parse { path, message } => {
+                write!(f, "Invalid config file {}: {}", path.display(), message.trim_end())
+            }
+            ConfigError::ZeroPort { setting } => write!(f, "{} must not be 0", setting),
+            ConfigError::SamePort(port) => {
+                write!(f, "tcp_port and web_port are both {}, the servers need one port each", port)
+            }
+            ConfigError::ZeroFoulLimit => write!(f, "foul_limit must be at least 1"),
+            ConfigError::InvalidLogLevel(level) => write!(
+                f,
+                "Invalid log level {:?}, expected off, error, warn, info, debug or trace",
+                level
+            ),
+            ConfigError::MissingStaticFile(path) => write!(
+                f,
+                "Overlay file {} not found, set static_dir to the directory holding overlay.html",
+                path.display()
+            ),
+            ConfigError::LogDirNotDirectory(path) => {
+                write!(f, "Log directory {} exists and is not a directory", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Read { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Why a log file could not be read.
+#[derive(Debug)]
+pub enum LogError {
+    /// The file could not be opened or read.
+    Read { path: PathBuf, error: std::io::Error },
+    /// A line doesn't hold bytes in the expected format.
+    InvalidLine { path: PathBuf, line: usize, message: String },
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Read { path, error } => write!(f, "Cannot read {}: {}", path.display(), error),
+            LogError::InvalidLine { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl std::error::Error for LogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LogError::Read { error, .. } => Some(error),
+            LogError::InvalidLine { .. } => None,
+        }
+    }
+}
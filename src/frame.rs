@@ -0,0 +1,421 @@
+//! Bodet frame codec: `SOH address STX CTRL message ETX LRC`.
+//!
+//! `parse_raw_data` validates a single frame, `FrameDecoder` cuts frames
+//! out of a byte stream such as a TCP connection. `ProtocolFrame::new` and
+//! `ProtocolFrame::to_bytes` go the other way and build frames for the wire.
+
+use log::debug;
+
+use crate::error::ParseError;
+
+/// Protocol control characters.
+pub const SOH: u8 = 0x01;
+pub const STX: u8 = 0x02;
+pub const ETX: u8 = 0x03;
+
+/// Address byte sent by the scorepad.
+pub const DEFAULT_ADDRESS: u8 = 0x7F;
+
+/// CTRL byte of data frames ('G').
+pub const CTRL_DATA: u8 = 0x47;
+
+/// What the CTRL byte of a frame says about its message.
+///
+/// The Scorepad document only asks to keep CTRL for the LRC, and the
+/// scorepad sends every game message with 'G'. Any other code is kept as is,
+/// so it can be reported rather than decoded as game data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Data,      // 'G', a game message
+    Other(u8), // not defined by the protocol document
+}
+
+impl Control {
+    pub fn from_byte(byte: u8) -> Self {
+        match byte {
+            CTRL_DATA => Control::Data,
+            other => Control::Other(other),
+        }
+    }
+}
+
+/// Upper bound on the length of a single frame on the wire. The longest
+/// scorepad message (37/38, rosters) is 32 bytes plus 6 bytes of framing.
+const MAX_FRAME_LEN: usize = 64;
+
+/// Represents a parsed protocol frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolFrame {
+    pub soh: u8,          // should be 0x01
+    pub address: u8,      // included in LRC calculation
+    pub stx: u8,          // should be 0x02
+    pub ctrl: u8,         // included in LRC calculation
+    pub message: Vec<u8>, // variable-length payload
+    pub etx: u8,          // should be 0x03
+    pub lrc: u8,          // 1 byte checksum as transmitted
+}
+
+impl ProtocolFrame {
+    /// Build a frame around a message, computing its LRC.
+    ///
+    /// ```
+    /// use scoreboard_rust::frame::{ProtocolFrame, CTRL_DATA, DEFAULT_ADDRESS};
+    ///
+    /// let frame = ProtocolFrame::new(DEFAULT_ADDRESS, CTRL_DATA, b"305 0  0 ".to_vec());
+    /// assert_eq!(
+    ///     frame.to_bytes(),
+    ///     [0x01, 0x7F, 0x02, 0x47, 0x33, 0x30, 0x35, 0x20, 0x30, 0x20, 0x20, 0x30, 0x20, 0x03, 0x2F]
+    /// );
+    /// ```
+    pub fn new(address: u8, ctrl: u8, message: Vec<u8>) -> Self {
+        let mut frame = Self {
+            soh: SOH,
+            address,
+            stx: STX,
+            ctrl,
+            message,
+            etx: ETX,
+            lrc: 0,
+        };
+        frame.lrc = frame.expected_lrc();
+        frame
+    }
+
+    /// What the CTRL byte says about the message.
+    pub fn control(&self) -> Control {
+        Control::from_byte(self.ctrl)
+    }
+
+    /// Serialize the frame to the bytes sent on the wire.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(6 + self.message.len());
+        v.push(self.soh);
+        v.extend_from_slice(&self.lrc_input_bytes());
+        v.push(self.lrc);
+        v
+    }
+
+    /// Compute LRC for a byte slice using the protocol rule:
+    /// XOR all bytes, mask with 0x7F, then if < 32 add 32.
+    ///
+    /// This function implements the canonical transformation and can be
+    /// used for both constructing and validating frames.
+    pub fn compute_lrc_bytes(bytes: &[u8]) -> u8 {
+        let mut xor: u8 = 0;
+        for &b in bytes {
+            xor ^= b;
+        }
+        let mut lrc = xor & 0x7F;
+        if lrc < 32 {
+            // use wrapping_add to be explicit about u8 arithmetic
+            lrc = lrc.wrapping_add(32);
+        }
+        lrc
+    }
+
+    /// Build the slice of bytes that are used for the LRC calculation:
+    /// Address, STX, CTRL, Message..., ETX (SOH excluded, ETX included).
+    fn lrc_input_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(3 + self.message.len() + 1);
+        v.push(self.address);
+        v.push(self.stx);
+        v.push(self.ctrl);
+        v.extend_from_slice(&self.message);
+        v.push(self.etx);
+        v
+    }
+
+    /// Compute the expected LRC for this frame (based on current fields).
+    pub fn expected_lrc(&self) -> u8 {
+        let bytes = self.lrc_input_bytes();
+        Self::compute_lrc_bytes(&bytes)
+    }
+
+    /// Validate the stored LRC against the computed value.
+    pub fn validate_lrc(&self) -> bool {
+        self.expected_lrc() == self.lrc
+    }
+}
+
+/// Parse raw byte data holding exactly one frame into a ProtocolFrame.
+///
+/// Use FrameDecoder to split a byte stream into frames first.
+pub fn parse_raw_data(data: &[u8]) -> Result<ProtocolFrame, ParseError> {
+    if data.len() < 6 {
+        return Err(ParseError::FrameTooShort { len: data.len() });
+    }
+
+    if data[0] != SOH {
+        return Err(ParseError::InvalidSoh(data[0]));
+    }
+
+    if data[2] != STX {
+        return Err(ParseError::InvalidStx(data[2]));
+    }
+
+    if data[data.len() - 2] != ETX {
+        return Err(ParseError::InvalidEtx(data[data.len() - 2]));
+    }
+
+    let soh = data[0];
+    let address = data[1];
+    let stx = data[2];
+    let ctrl = data[3];
+    let message = data[4..data.len() - 2].to_vec();
+    let etx = data[data.len() - 2];
+    let lrc = data[data.len() - 1];
+
+    let frame = ProtocolFrame {
+        soh,
+        address,
+        stx,
+        ctrl,
+        message,
+        etx,
+        lrc,
+    };
+
+    if !frame.validate_lrc() {
+        return Err(ParseError::LrcMismatch {
+            expected: frame.expected_lrc(),
+            received: frame.lrc,
+        });
+    }
+
+    Ok(frame)
+}
+
+/// Reassembles ProtocolFrames from a byte stream, independently of how
+/// the stream was split into TCP reads.
+///
+/// Bytes are appended with `push` and complete frames are taken out with
+/// `next_frame`. Bytes before a SOH are skipped, partial frames are kept
+/// until the rest arrives and several frames in one read are split apart.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append freshly received bytes to the internal buffer.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Take the next frame out of the buffer.
+    ///
+    /// Returns `None` when more bytes are needed, `Some(Ok(frame))` for a
+    /// valid frame and `Some(Err(_))` when a broken frame was dropped while
+    /// resyncing on the next SOH.
+    pub fn next_frame(&mut self) -> Option<Result<ProtocolFrame, ParseError>> {
+        // Skip anything in front of the next SOH
+        match self.buffer.iter().position(|&b| b == SOH) {
+            Some(0) => {}
+            Some(start) => {
+                debug!("Skipping {} bytes before SOH: {:02X?}", start, &self.buffer[..start]);
+                self.buffer.drain(..start);
+            }
+            None => {
+                if !self.buffer.is_empty() {
+                    debug!("Skipping {} bytes without SOH: {:02X?}", self.buffer.len(), self.buffer);
+                }
+                self.buffer.clear();
+                return None;
+            }
+        }
+
+        if self.buffer.len() < 3 {
+            return None;
+        }
+
+        if self.buffer[2] != STX {
+            // Not a frame start after all, resync on the next SOH
+            let stx = self.buffer[2];
+            self.buffer.drain(..1);
+            return Some(Err(ParseError::InvalidStx(stx)));
+        }
+
+        if self.buffer.len() < 5 {
+            return None;
+        }
+
+        // A SOH followed by STX before the ETX starts the next frame, so
+        // this one was cut off and must not be joined to it.
+        let next_start = (1..self.buffer.len()).find(|&i| self.buffer[i] == SOH && self.buffer.get(i + 2) == Some(&STX));
+        // A next frame inside the header leaves no message to search, and
+        // is dropped to below as a truncated frame
+        let limit = next_start.unwrap_or(self.buffer.len()).max(4);
+
+        if let Some(offset) = self.buffer[4..limit].iter().position(|&b| b == ETX) {
+            let end = 4 + offset + 2; // ETX + LRC
+            if end > MAX_FRAME_LEN {
+                self.skip_to_next_soh();
+                return Some(Err(ParseError::FrameTooLong { max_len: MAX_FRAME_LEN }));
+            }
+            if end > self.buffer.len() {
+                // Wait for the LRC byte
+                return None;
+            }
+
+            let result = parse_raw_data(&self.buffer[..end]);
+            if result.is_ok() {
+                self.buffer.drain(..end);
+            } else {
+                self.skip_to_next_soh();
+            }
+            return Some(result);
+        }
+
+        if let Some(start) = next_start {
+            self.buffer.drain(..start);
+            return Some(Err(ParseError::TruncatedFrame { len: start }));
+        }
+
+        if self.buffer.len() >= MAX_FRAME_LEN {
+            self.skip_to_next_soh();
+            return Some(Err(ParseError::FrameTooLong { max_len: MAX_FRAME_LEN }));
+        }
+
+        None
+    }
+
+    /// Drop a broken frame: everything up to the next SOH after the one it
+    /// starts with.
+    fn skip_to_next_soh(&mut self) {
+        let next = self.buffer[1..]
+            .iter()
+            .position(|&b| b == SOH)
+            .map_or(self.buffer.len(), |offset| offset + 1);
+        self.buffer.drain(..next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Message 30, Home 80 - Away 74, as captured from the scorepad.
+    const SCORE: [u8; 15] = [
+        0x01, 0x7F, 0x02, 0x47, 0x33, 0x30, 0x35, 0x20, 0x38, 0x30, 0x37, 0x34, 0x20, 0x03, 0x24,
+    ];
+
+    fn clock() -> Vec<u8> {
+        ProtocolFrame::new(DEFAULT_ADDRESS, CTRL_DATA, b"18\x805 15410  1 ".to_vec()).to_bytes()
+    }
+
+    /// Everything the decoder makes of `reads`, pushed one after the other.
+    fn decode(reads: &[&[u8]]) -> Vec<Result<ProtocolFrame, ParseError>> {
+        let mut decoder = FrameDecoder::new();
+        let mut results = Vec::new();
+        for read in reads {
+            decoder.push(read);
+            while let Some(result) = decoder.next_frame() {
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    fn messages(results: &[Result<ProtocolFrame, ParseError>]) -> Vec<Vec<u8>> {
+        results.iter().flatten().map(|frame| frame.message.clone()).collect()
+    }
+
+    #[test]
+    fn frame_split_across_reads() {
+        let (first, second) = SCORE.split_at(6);
+        let results = decode(&[first, second]);
+        assert_eq!(messages(&results), [b"305 8074 ".to_vec()]);
+        assert!(results.iter().all(Result::is_ok));
+    }
+
+    #[test]
+    fn several_frames_in_one_read() {
+        let read = [SCORE.to_vec(), clock(), SCORE.to_vec()].concat();
+        let results = decode(&[&read]);
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(results[1].as_ref().unwrap().message, b"18\x805 15410  1 ");
+    }
+
+    #[test]
+    fn leading_garbage_is_skipped() {
+        let read = [b"\x00\xFFxx\x03".to_vec(), SCORE.to_vec()].concat();
+        let results = decode(&[&read]);
+        assert_eq!(messages(&results), [b"305 8074 ".to_vec()]);
+        assert!(results.iter().all(Result::is_ok));
+    }
+
+    #[test]
+    fn truncated_frame_followed_by_a_valid_one() {
+        let read = [SCORE[..8].to_vec(), clock()].concat();
+        let results = decode(&[&read]);
+        assert!(matches!(results[0], Err(ParseError::TruncatedFrame { len: 8 })));
+        assert_eq!(messages(&results), [b"18\x805 15410  1 ".to_vec()]);
+    }
+
+    #[test]
+    fn frame_without_lrc_followed_by_a_valid_one() {
+        let read = [SCORE[..14].to_vec(), clock()].concat();
+        let results = decode(&[&read]);
+        assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+        assert_eq!(messages(&results), [b"18\x805 15410  1 ".to_vec()]);
+    }
+
+    #[test]
+    fn bad_lrc_frame_followed_by_a_valid_one() {
+        let mut corrupt = SCORE.to_vec();
+        corrupt[14] ^= 0x01;
+        let read = [corrupt, b",xx".to_vec(), clock()].concat();
+        let results = decode(&[&read]);
+        assert!(matches!(results[0], Err(ParseError::LrcMismatch { .. })));
+        assert_eq!(messages(&results), [b"18\x805 15410  1 ".to_vec()]);
+    }
+
+    #[test]
+    fn next_frame_inside_the_header() {
+        for read in [&b"\x01\x01\x02\x02\x33"[..], &b"\x01\x7F\x02\x01\x33\x02\x00"[..]] {
+            let mut decoder = FrameDecoder::new();
+            decoder.push(read);
+            assert!(matches!(decoder.next_frame(), Some(Err(ParseError::TruncatedFrame { .. }))));
+            decoder.push(&SCORE);
+            let results: Vec<_> = std::iter::from_fn(|| decoder.next_frame()).collect();
+            assert_eq!(messages(&results).last(), Some(&b"305 8074 ".to_vec()), "after {:02X?}", read);
+        }
+    }
+
+    #[test]
+    fn control_bytes_in_any_order_never_panic() {
+        let alphabet = [SOH, STX, ETX, b'3'];
+        for n in 0..alphabet.len().pow(7) {
+            let read: Vec<u8> = (0..7).map(|digit| alphabet[n / alphabet.len().pow(digit) % alphabet.len()]).collect();
+            decode(&[&read]);
+        }
+    }
+
+    #[test]
+    fn dropped_bytes_never_join_two_frames() {
+        let stream: Vec<u8> = (0..5).flat_map(|_| [SCORE.to_vec(), clock()].concat()).collect();
+        for dropped in 0..stream.len() {
+            let mut read = stream.clone();
+            read.remove(dropped);
+            let results = decode(&[&read]);
+            // The LRC can't catch every dropped byte, so the damaged frame
+            // itself may pass; it must not swallow a neighbour though.
+            let frames: Vec<&ProtocolFrame> = results.iter().flatten().collect();
+            let intact = frames
+                .iter()
+                .filter(|frame| frame.message == b"305 8074 " || frame.message == b"18\x805 15410  1 ")
+                .count();
+            assert!(intact >= 9, "dropping byte {} lost a valid frame", dropped);
+            assert!(
+                frames.iter().all(|frame| !frame.message.contains(&SOH)),
+                "dropping byte {} joined two frames",
+                dropped
+            );
+        }
+    }
+}
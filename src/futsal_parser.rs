@@ -0,0 +1,94 @@
+//! Futsal messages of the Bodet Scorepad protocol.
+//!
+//! Futsal sends everything in message 45, which carries no sports id byte.
+//! Same conventions as `basketball_parser`, and `GameState::apply_futsal`
+//! updates the state from it.
+
+use crate::basketball_parser::{check_digits, check_len, check_type_len};
+use crate::error::ParseError;
+use crate::frame::ProtocolFrame;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message45 {
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub status_word: u8,        // Status word byte, b0-b2 as in basketball message 18
+    pub minutes_1: u8,          // Minutes * 10
+    pub minutes_2: u8,          // Minutes * 1
+    pub seconds_1: u8,          // Seconds * 10
+    pub seconds_2: u8,          // Seconds * 1
+    pub tenths: u8,             // Seconds * 0.1
+    pub home_score_1: u8,       // Home score position 1
+    pub home_score_2: u8,       // Home score position 2
+    pub home_score_3: u8,       // Home score position 3
+    pub guest_score_1: u8,      // Guest score position 1
+    pub guest_score_2: u8,      // Guest score position 2
+    pub guest_score_3: u8,      // Guest score position 3
+    pub period: u8,             // Period number, 'E' during extra time
+    pub extra_time: u8,         // Extra time number
+}
+
+/// A decoded futsal message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FutsalMessage {
+    /// Message 45: game clock, scores and period.
+    Message45(Message45),
+}
+
+impl TryFrom<&ProtocolFrame> for FutsalMessage {
+    type Error = ParseError;
+
+    fn try_from(frame: &ProtocolFrame) -> Result<Self, Self::Error> {
+        let message = &frame.message;
+
+        check_type_len(message)?;
+
+        match (message[0], message[1]) {
+            // Message Type 45
+            (0x34, 0x35) => {
+                check_len(45, message, 16)?;
+
+                let decoded = Message45 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    minutes_1: message[3],
+                    minutes_2: message[4],
+                    seconds_1: message[5],
+                    seconds_2: message[6],
+                    tenths: message[7],
+                    home_score_1: message[8],
+                    home_score_2: message[9],
+                    home_score_3: message[10],
+                    guest_score_1: message[11],
+                    guest_score_2: message[12],
+                    guest_score_3: message[13],
+                    period: message[14],
+                    extra_time: message[15],
+                };
+
+                check_digits(
+                    45,
+                    &[
+                        ("minutes_1", decoded.minutes_1),
+                        ("minutes_2", decoded.minutes_2),
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                        ("tenths", decoded.tenths),
+                        ("home_score_1", decoded.home_score_1),
+                        ("home_score_2", decoded.home_score_2),
+                        ("home_score_3", decoded.home_score_3),
+                        ("guest_score_1", decoded.guest_score_1),
+                        ("guest_score_2", decoded.guest_score_2),
+                        ("guest_score_3", decoded.guest_score_3),
+                        ("extra_time", decoded.extra_time),
+                    ],
+                )?;
+
+                Ok(FutsalMessage::Message45(decoded))
+            }
+
+            _ => Err(ParseError::UnknownMessageType(message[0], message[1])),
+        }
+    }
+}
@@ -0,0 +1,1609 @@
+//! Game state kept by the server and served to overlays.
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::basketball_parser::{ascii_number, ascii_text, foul_indicator_count, BodetMessage, StatusWord18, StatusWord50};
+use crate::error::ParseError;
+use crate::frame::ProtocolFrame;
+use crate::futsal_parser::FutsalMessage;
+use crate::handball_parser::{HandballMessage, SUSPENSION_SLOTS};
+use crate::period::{regulation_periods, Interval, Period, PeriodFormat};
+use crate::sport::{Sport, SportMessage};
+use crate::volleyball_parser::{StatusWordVolleyball, VolleyballMessage};
+
+/// One row of a team's player table, as kept by the scorepad.
+///
+/// The scorepad reports points by shirt number (message 56) and personal
+/// fouls by line of its player panel (messages 33/34), so a row may know
+/// only one of the two until both have been seen.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub line: Option<u8>,       // line on the scorepad player panel, 1-based
+    pub number: Option<String>, // shirt number
+    pub points: Option<u16>,
+    pub fouls: Option<u8>,
+}
+
+/// A shirt number entered on a line of the scorepad player panel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RosterEntry {
+    pub line: u8,       // 1-based
+    pub number: String,
+}
+
+/// Shirt numbers entered on the scorepad for one team (messages 37/38).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Roster {
+    pub players: Vec<RosterEntry>,
+}
+
+impl Roster {
+    /// Build a roster from the 2-character shirt numbers of each line.
+    /// Blank lines are left out.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let players = bytes
+            .chunks_exact(2)
+            .enumerate()
+            .filter_map(|(index, pair)| {
+                ascii_text(pair).map(|number| RosterEntry {
+                    line: index as u8 + 1,
+                    number,
+                })
+            })
+            .collect();
+        Self { players }
+    }
+}
+
+/// Re-key a team's player table on a new roster, so every roster line gets
+/// one row holding both its shirt number and its panel line. Points follow
+/// the shirt number and fouls follow the line. Returns whether anything
+/// changed.
+pub(crate) fn apply_roster(players: &mut Vec<PlayerStats>, roster: &Roster) -> bool {
+    let mut remaining = std::mem::take(players);
+    let mut rebuilt = Vec::with_capacity(roster.players.len());
+
+    for entry in &roster.players {
+        let by_number = remaining
+            .iter()
+            .position(|p| p.number.as_deref() == Some(entry.number.as_str()))
+            .map(|index| remaining.remove(index));
+        let by_line = remaining
+            .iter()
+            .position(|p| p.line == Some(entry.line))
+            .map(|index| remaining.remove(index));
+
+        let fouls = match (&by_number, &by_line) {
+            (_, Some(row)) => row.fouls,
+            (Some(row), None) if row.line == Some(entry.line) => row.fouls,
+            _ => None,
+        };
+
+        rebuilt.push(PlayerStats {
+            line: Some(entry.line),
+            number: Some(entry.number.clone()),
+            points: by_number.and_then(|row| row.points),
+            fouls,
+        });
+    }
+
+    // Players no longer on the roster keep their row, without a line
+    rebuilt.extend(remaining.into_iter().filter(|p| p.number.is_some()).map(|p| PlayerStats {
+        line: None,
+        ..p
+    }));
+
+    let changed = *players != rebuilt;
+    *players = rebuilt;
+    changed
+}
+
+/// Side of the court a player or indicator belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Team {
+    Home,
+    Away,
+}
+
+impl Team {
+    /// Decode the scorepad team byte: 0x31 home, 0x32 guest.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x31 => Some(Team::Home),
+            0x32 => Some(Team::Away),
+            _ => None,
+        }
+    }
+}
+
+/// Personal foul limit used when none is configured (FIBA rules).
+pub const DEFAULT_FOUL_LIMIT: u8 = 5;
+
+/// One personal foul call shown on the scorepad (message 31).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FoulRecord {
+    pub team: Team,
+    pub number: String,  // shirt number
+    pub fouls: u8,       // personal fouls of the player after this call
+    pub period: String,  // period name at the time of the call
+    pub time: String,    // game clock at the time of the call
+    pub fouled_out: bool, // this call made the player reach the foul limit
+}
+
+/// Every personal foul call of the game, in the order they were shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FoulLedger {
+    pub limit: u8, // personal fouls after which a player is fouled out
+    pub records: Vec<FoulRecord>,
+}
+
+impl Default for FoulLedger {
+    fn default() -> Self {
+        Self::new(DEFAULT_FOUL_LIMIT)
+    }
+}
+
+impl FoulLedger {
+    pub fn new(limit: u8) -> Self {
+        Self {
+            limit,
+            records: Vec::new(),
+        }
+    }
+
+    /// Latest personal foul count recorded for a player.
+    pub fn fouls_of(&self, team: Team, number: &str) -> Option<u8> {
+        self.records
+            .iter()
+            .rev()
+            .find(|r| r.team == team && r.number == number)
+            .map(|r| r.fouls)
+    }
+
+    /// Whether a player has reached the foul limit.
+    pub fn is_fouled_out(&self, team: Team, number: &str) -> bool {
+        self.fouls_of(team, number).is_some_and(|fouls| fouls >= self.limit)
+    }
+
+    /// Record a foul call, unless it is a repeat of the last call recorded
+    /// for that player (the scorepad keeps sending it for about 10 s).
+    /// Returns the new record.
+    pub(crate) fn record(&mut self, team: Team, number: &str, fouls: u8, period: &str, time: &str) -> Option<&FoulRecord> {
+        if self.fouls_of(team, number) == Some(fouls) {
+            return None;
+        }
+
+        let fouled_out = fouls >= self.limit && !self.is_fouled_out(team, number);
+        self.records.push(FoulRecord {
+            team,
+            number: number.to_string(),
+            fouls,
+            period: period.to_string(),
+            time: time.to_string(),
+            fouled_out,
+        });
+        self.records.last()
+    }
+}
+
+/// Find the row of a shirt number, adding it when the player is new.
+pub(crate) fn player_by_number<'a>(players: &'a mut Vec<PlayerStats>, number: &str) -> &'a mut PlayerStats {
+    let index = match players.iter().position(|p| p.number.as_deref() == Some(number)) {
+        Some(index) => index,
+        None => {
+            players.push(PlayerStats {
+                number: Some(number.to_string()),
+                ..Default::default()
+            });
+            players.len() - 1
+        }
+    };
+    &mut players[index]
+}
+
+/// Set the points of a shirt number. Returns whether anything changed.
+pub(crate) fn apply_player_points(players: &mut Vec<PlayerStats>, number: &str, points: u16) -> bool {
+    let player = player_by_number(players, number);
+    if player.points == Some(points) {
+        return false;
+    }
+    player.points = Some(points);
+    true
+}
+
+/// Set the personal fouls of one panel line. Returns whether anything changed.
+pub(crate) fn apply_line_foul(players: &mut Vec<PlayerStats>, line: u8, fouls: u8) -> bool {
+    match players.iter_mut().find(|p| p.line == Some(line)) {
+        Some(player) if player.fouls != Some(fouls) => {
+            player.fouls = Some(fouls);
+            true
+        }
+        Some(_) => false,
+        // Don't fill the table with empty lines
+        None if fouls > 0 => {
+            players.push(PlayerStats {
+                line: Some(line),
+                fouls: Some(fouls),
+                ..Default::default()
+            });
+            true
+        }
+        None => false,
+    }
+}
+
+/// Update the personal fouls of every panel line from a foul indicator list.
+/// Returns whether anything changed.
+pub(crate) fn apply_line_fouls(players: &mut Vec<PlayerStats>, indicators: &[u8]) -> bool {
+    let mut changed = false;
+    for (index, &indicator) in indicators.iter().enumerate() {
+        changed |= apply_line_foul(players, index as u8 + 1, foul_indicator_count(indicator));
+    }
+    changed
+}
+
+/// A running 2-minute suspension (handball message 03).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suspension {
+    pub number: Option<String>, // shirt number of the suspended player
+    pub time: String,           // time left, "M:SS"
+}
+
+/// Score of an ended volleyball set (message 08).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetScore {
+    pub home: u16,
+    pub away: u16,
+}
+
+/// Which clock message 18 carries (bit 0 of its status word).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockType {
+    Game, // game clock
+    Rest, // rest timer, between periods and before the match
+}
+
+/// Direction the game clock runs in.
+///
+/// The protocol doesn't send it, so it is inferred from consecutive values
+/// while the clock runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockDirection {
+    Up,
+    Down,
+}
+
+/// Largest step between two clock values, in tenths, that is taken as the
+/// clock running rather than being set by the operator.
+const MAX_CLOCK_STEP: u32 = 20;
+
+/// What the shot clock display is doing (message 50).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShotClockState {
+    Running,
+    Stopped, // timer off, the display shows the frozen value
+    Blank,   // display switched off by the console
+}
+
+/// Horns driven by the scorepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Horn {
+    Game,      // game clock horn (message 18)
+    ShotClock, // shot clock horn (message 50)
+}
+
+/// A horn started or stopped sounding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HornEvent {
+    pub horn: Horn,
+    pub on: bool,       // true when the horn starts, false when it stops
+    pub period: String, // period name at the time of the horn
+    pub time: String,   // game clock at the time of the horn
+}
+
+/// Phase of the game, inferred from the clocks, the period and the horns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GamePhase {
+    PreGame,     // until the game clock first runs
+    InPeriod,    // a regulation period is being played
+    EndOfPeriod, // the period is over, the next one hasn't started
+    Halftime,
+    Overtime,    // an overtime is being played
+    Final,       // regulation or an overtime ended with a winner
+}
+
+/// The game went from one phase to another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseChange {
+    pub from: GamePhase,
+    pub to: GamePhase,
+    pub period: String, // period name at the time of the change
+    pub time: String,   // game clock at the time of the change
+}
+
+/// Names and final score of a game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub home_name: Option<String>,
+    pub away_name: Option<String>,
+    pub home_score: String,
+    pub away_score: String,
+}
+
+/// Something that happened in the game besides the state changing, pushed
+/// to SSE clients as a named event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GameEvent {
+    /// A player reached the personal foul limit.
+    FouledOut(FoulRecord),
+    /// The game or shot clock horn started or stopped.
+    Horn(HornEvent),
+    /// The scorepad started a new match; carries the result of the
+    /// finished game.
+    NewGame(GameResult),
+    /// The game phase changed.
+    Phase(PhaseChange),
+}
+
+impl GameEvent {
+    /// Event name on the SSE stream.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::FouledOut(_) => "fouled_out",
+            GameEvent::Horn(_) => "horn",
+            GameEvent::NewGame(_) => "new_game",
+            GameEvent::Phase(_) => "phase",
+        }
+    }
+}
+
+/// Values as the scorepad displays them: the presentation layer of the
+/// game state, and the top-level fields of API version 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Display {
+    pub home_score: String,
+    pub away_score: String,
+    pub time: String, // clock as "M:SS", or "S.t" in the last minute when sent in tenths
+    pub period_name: String,
+    pub home_fouls: String,
+    pub away_fouls: String,
+    pub home_timeouts: String,
+    pub away_timeouts: String,
+    pub shot_clock: Option<String>,
+    pub timeout_clock: Option<String>, // time-out countdown, None when no time-out runs
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self {
+            home_score: "-".to_string(),
+            away_score: "-".to_string(),
+            time: "--:--".to_string(),
+            period_name: "-".to_string(),
+            home_fouls: "-".to_string(),
+            away_fouls: "-".to_string(),
+            home_timeouts: "-".to_string(),
+            away_timeouts: "-".to_string(),
+            shot_clock: None,
+            timeout_clock: None,
+        }
+    }
+}
+
+/// Current state of the game. Numbers are typed and `None` until the
+/// scorepad sent them; `display` holds the same values as shown on the
+/// scorepad.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub sport: Option<Sport>, // sport of the last message carrying one
+    pub home_score: Option<u16>,
+    pub away_score: Option<u16>,
+    pub clock_tenths: Option<u32>,               // game clock, in tenths of a second
+    pub clock_in_tenths: bool,                   // `clock_tenths` was sent to the tenth, not in whole seconds
+    pub clock_type: Option<ClockType>,           // None before the first message 18
+    pub clock_direction: Option<ClockDirection>, // None until the clock was seen running
+    pub period: Option<Period>,                  // None before the match
+    pub period_format: PeriodFormat,             // basketball quarters or halves, configured
+    pub home_fouls: Option<u8>,                  // team fouls
+    pub away_fouls: Option<u8>,
+    pub home_timeouts: Option<u8>,
+    pub away_timeouts: Option<u8>,
+    pub game_state: String, // "pre-game", then "running" or "paused" with the game clock
+    pub phase: GamePhase,
+    pub shot_clock_tenths: Option<u32>,
+    pub shot_clock_state: Option<ShotClockState>, // None before the first message 50
+    pub home_name: Option<String>, // as typed on the scorepad
+    pub away_name: Option<String>,
+    pub home_players: Vec<PlayerStats>,
+    pub away_players: Vec<PlayerStats>,
+    pub home_roster: Roster,
+    pub away_roster: Roster,
+    pub player_fouls: FoulLedger,
+    pub timeout_seconds: Option<u16>,  // time-out countdown, None when no time-out runs
+    pub timeout_team: Option<Team>,    // team that called the running time-out
+    pub time_of_day: Option<String>,   // scorepad wall clock, "HH:MM"
+    pub home_bonus: bool,              // team foul indicator lit
+    pub away_bonus: bool,
+    pub game_horn: bool,               // game clock horn sounding
+    pub shot_clock_horn: bool,         // shot clock horn sounding
+    pub new_match: Option<bool>,       // new match bit of the last message 18
+    #[serde(skip)]
+    pub event_log: Vec<GameEvent>,     // events raised during this game
+    pub serving: Option<Team>,         // volleyball: team at service
+    pub home_sets: Option<u8>,         // volleyball: sets won
+    pub away_sets: Option<u8>,
+    pub set_scores: Vec<SetScore>,     // volleyball: scores of the ended sets
+    pub home_suspensions: Vec<Suspension>, // handball: running 2-minute suspensions
+    pub away_suspensions: Vec<Suspension>,
+    pub display: Display,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self {
+            sport: None,
+            home_score: None,
+            away_score: None,
+            clock_tenths: None,
+            clock_in_tenths: false,
+            clock_type: None,
+            clock_direction: None,
+            period: None,
+            period_format: PeriodFormat::default(),
+            home_fouls: None,
+            away_fouls: None,
+            home_timeouts: None,
+            away_timeouts: None,
+            game_state: "pre-game".to_string(),
+            phase: GamePhase::PreGame,
+            shot_clock_tenths: None,
+            shot_clock_state: None,
+            home_name: None,
+            away_name: None,
+            home_players: Vec::new(),
+            away_players: Vec::new(),
+            home_roster: Roster::default(),
+            away_roster: Roster::default(),
+            player_fouls: FoulLedger::default(),
+            timeout_seconds: None,
+            timeout_team: None,
+            time_of_day: None,
+            home_bonus: false,
+            away_bonus: false,
+            game_horn: false,
+            shot_clock_horn: false,
+            new_match: None,
+            event_log: Vec::new(),
+            serving: None,
+            home_sets: None,
+            away_sets: None,
+            set_scores: Vec::new(),
+            home_suspensions: Vec::new(),
+            away_suspensions: Vec::new(),
+            display: Display::default(),
+        }
+    }
+}
+
+/// A finished game, archived when the scorepad starts a new match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameArchive {
+    pub state: GameState,        // state at the end of the game
+    pub events: Vec<GameEvent>,  // events raised during the game, in order
+}
+
+impl GameArchive {
+    /// The `new_game` event announcing the end of this game.
+    pub fn new_game_event(&self) -> GameEvent {
+        GameEvent::NewGame(GameResult {
+            home_name: self.state.home_name.clone(),
+            away_name: self.state.away_name.clone(),
+            home_score: self.state.display.home_score.clone(),
+            away_score: self.state.display.away_score.clone(),
+        })
+    }
+}
+
+/// Result of applying one message to a GameState.
+#[derive(Debug, Default)]
+pub struct StateUpdate {
+    pub changed: bool,          // the state differs from before the message
+    pub events: Vec<GameEvent>, // events raised by the message, in order
+    pub finished_game: Option<GameArchive>, // game closed by a new match
+    pub error: Option<ParseError>,          // why the frame was not applied
+}
+
+impl GameState {
+    /// Track the state of a horn and raise an event when it starts or stops.
+    fn update_horn(&mut self, horn: Horn, on: bool, events: &mut Vec<GameEvent>) {
+        let sounding = match horn {
+            Horn::Game => &mut self.game_horn,
+            Horn::ShotClock => &mut self.shot_clock_horn,
+        };
+        if *sounding == on {
+            return;
+        }
+        *sounding = on;
+
+        info!("{:?} horn {}", horn, if on { "on" } else { "off" });
+        events.push(GameEvent::Horn(HornEvent {
+            horn,
+            on,
+            period: self.display.period_name.clone(),
+            time: self.display.time.clone(),
+        }));
+    }
+
+    /// Update the running time-out from a countdown and the time-out
+    /// indicators. Returns whether anything changed.
+    fn update_timeout(&mut self, home_indicator: u8, guest_indicator: u8, seconds: [u8; 2]) -> bool {
+        // The countdown shows 00 when no time-out is running
+        let seconds = ascii_number(&seconds).unwrap_or(0);
+        let (timeout_seconds, timeout_team) = if seconds == 0 {
+            (None, None)
+        } else {
+            // The indicator of the team in time-out blinks between its
+            // count and count - 1, so it differs from the clock message count
+            // every other frame; keep the last known team in between.
+            let home_blinking = digit_value(home_indicator) != self.home_timeouts;
+            let guest_blinking = digit_value(guest_indicator) != self.away_timeouts;
+            let team = match (home_blinking, guest_blinking) {
+                (true, false) => Some(Team::Home),
+                (false, true) => Some(Team::Away),
+                _ => self.timeout_team,
+            };
+            (Some(seconds), team)
+        };
+
+        if self.timeout_seconds == timeout_seconds && self.timeout_team == timeout_team {
+            return false;
+        }
+        info!("Time-out: {:?} {:?}", timeout_team, timeout_seconds);
+        self.timeout_seconds = timeout_seconds;
+        self.timeout_team = timeout_team;
+        self.display.timeout_clock = timeout_seconds.map(|seconds| seconds.to_string());
+        true
+    }
+
+    /// Set the scores from their digits, as sent by the scorepad.
+    fn update_scores(&mut self, home: &[u8], guest: &[u8]) {
+        self.home_score = ascii_number(home);
+        self.away_score = ascii_number(guest);
+        self.display.home_score = home.iter().map(|&b| b as char).collect();
+        self.display.away_score = guest.iter().map(|&b| b as char).collect();
+    }
+
+    /// Set the time-outs taken from their digits.
+    fn update_timeouts(&mut self, home: u8, guest: u8) {
+        self.home_timeouts = digit_value(home);
+        self.away_timeouts = digit_value(guest);
+        self.display.home_timeouts = (home as char).to_string();
+        self.display.away_timeouts = (guest as char).to_string();
+    }
+
+    /// Set the period from its byte: a digit, or a letter for overtime.
+    /// Must follow the clock update of the same message, which tells a
+    /// break from a period.
+    fn update_period(&mut self, sport: Sport, byte: u8, extra_time: Option<u8>) {
+        let clock_type = self.clock_type.unwrap_or(ClockType::Game);
+        let period = Period::from_byte(self.period.as_ref(), sport, self.period_format, byte, extra_time, clock_type);
+        let name = period.as_ref().map(|p| p.name(sport)).unwrap_or_default();
+        if name != self.display.period_name {
+            info!("Period: {:?} {:?}", name, period.as_ref().map(|p| p.number));
+        }
+        self.period = period;
+        self.display.period_name = name;
+    }
+
+    /// Update the clock from a game clock sent as MM:SS, with tenths for
+    /// sports that send them.
+    fn update_game_clock(&mut self, clock_off: bool, clock_type: ClockType, minutes: [u8; 2], seconds: [u8; 2], tenths: Option<u8>) {
+        self.game_state = if clock_off { "paused" } else { "running" }.to_string();
+
+        let minutes = ascii_number(&minutes).unwrap_or(0);
+        let clock_tenths = ascii_number(&seconds).map(|seconds| {
+            let tenths = tenths.and_then(|t| ascii_number(&[t])).unwrap_or(0);
+            (minutes as u32 * 60 + seconds as u32) * 10 + tenths as u32
+        });
+        self.update_clock(clock_type, clock_tenths, tenths.is_some());
+    }
+
+    /// Record a new clock value, learn the clock direction from it and show
+    /// it. This is the only place the clock is set, whatever message it
+    /// comes from.
+    ///
+    /// `in_tenths` values are precise to the tenth. A whole-second value
+    /// that agrees with the last one in tenths, as message 18 sends next to
+    /// message 36 in the last minute, doesn't replace it.
+    fn update_clock(&mut self, clock_type: ClockType, tenths: Option<u32>, in_tenths: bool) {
+        if let (false, true, Some(previous), Some(current)) = (in_tenths, self.clock_in_tenths, self.clock_tenths, tenths) {
+            if self.clock_type == Some(clock_type) && previous.abs_diff(current) < 10 {
+                return;
+            }
+        }
+
+        let running = clock_type == ClockType::Game
+            && self.clock_type == Some(clock_type)
+            && self.game_state == "running";
+        if let (true, Some(previous), Some(current)) = (running, self.clock_tenths, tenths) {
+            if previous != current && previous.abs_diff(current) <= MAX_CLOCK_STEP {
+                let direction = if current > previous { ClockDirection::Up } else { ClockDirection::Down };
+                if self.clock_direction != Some(direction) {
+                    info!("Game clock counts {:?}", direction);
+                    self.clock_direction = Some(direction);
+                }
+            }
+        }
+
+        self.clock_type = Some(clock_type);
+        self.clock_tenths = tenths;
+        self.clock_in_tenths = in_tenths && tenths.is_some();
+        self.display.time = format_clock(tenths, self.clock_in_tenths);
+    }
+
+    /// Whether the game clock has run out.
+    ///
+    /// Only a count-down clock runs out; the end of a period on a count-up
+    /// clock is only known from the horn or the period changing. A clock of
+    /// unknown direction is taken to count down, as in regulation games.
+    pub fn clock_expired(&self) -> bool {
+        self.clock_type == Some(ClockType::Game)
+            && self.clock_direction != Some(ClockDirection::Up)
+            && self.clock_tenths == Some(0)
+    }
+
+    /// Whether the game clock is shown and running.
+    fn clock_running(&self) -> bool {
+        self.clock_type == Some(ClockType::Game) && self.game_state == "running"
+    }
+
+    /// Whether the current period is over while its clock is still shown:
+    /// a count-down clock ran out, or the horn sounds on a stopped count-up
+    /// clock.
+    fn period_over(&self) -> bool {
+        self.clock_expired()
+            || (self.clock_direction == Some(ClockDirection::Up) && self.game_horn && !self.clock_running())
+    }
+
+    /// Whether the game has a winner once the current period is over.
+    fn decided(&self, period: &Period) -> bool {
+        let Some(sport) = self.sport else {
+            return false;
+        };
+        period.number >= regulation_periods(sport, self.period_format)
+            && self.home_score.is_some()
+            && self.home_score != self.away_score
+    }
+
+    /// Phase the game is in after the last message.
+    fn next_phase(&self) -> GamePhase {
+        let running = self.clock_running();
+        let Some(period) = &self.period else {
+            // Blank period, as before the match
+            return self.phase;
+        };
+        if !running && matches!(self.phase, GamePhase::PreGame | GamePhase::Final) {
+            return self.phase;
+        }
+
+        // Volleyball sets end on points, best of 5
+        if self.sport == Some(Sport::Volleyball) {
+            return if self.home_sets.max(self.away_sets) >= Some(3) { GamePhase::Final } else { GamePhase::InPeriod };
+        }
+
+        match period.interval {
+            Some(Interval::Halftime) => GamePhase::Halftime,
+            Some(Interval::Break) if self.decided(period) => GamePhase::Final,
+            Some(Interval::Break) => GamePhase::EndOfPeriod,
+            // The clock may still be flagged as running at 0.0
+            None if self.period_over() && self.decided(period) => GamePhase::Final,
+            None if self.period_over() => GamePhase::EndOfPeriod,
+            None if running && period.overtime.is_some() => GamePhase::Overtime,
+            None if running => GamePhase::InPeriod,
+            // Clock stopped within the period, or set for the next one
+            None => self.phase,
+        }
+    }
+
+    /// Move to the phase the last message puts the game in, raising an
+    /// event on a change. Returns whether the phase changed.
+    fn update_phase(&mut self, events: &mut Vec<GameEvent>) -> bool {
+        let phase = self.next_phase();
+        if phase == self.phase {
+            return false;
+        }
+
+        info!("Game phase: {:?} -> {:?}", self.phase, phase);
+        let change = GameEvent::Phase(PhaseChange {
+            from: self.phase,
+            to: phase,
+            period: self.display.period_name.clone(),
+            time: self.display.time.clone(),
+        });
+        self.event_log.push(change.clone());
+        events.push(change);
+        self.phase = phase;
+        true
+    }
+
+    /// Replace the state with a fresh one for a new match, keeping the foul
+    /// limit and period format, and return the finished game.
+    fn start_new_game(&mut self) -> GameArchive {
+        let fresh = GameState {
+            player_fouls: FoulLedger::new(self.player_fouls.limit),
+            period_format: self.period_format,
+            ..GameState::default()
+        };
+        let mut state = std::mem::replace(self, fresh);
+        let events = std::mem::take(&mut state.event_log);
+        GameArchive { state, events }
+    }
+
+    /// Decode a valid frame and apply the message it carries.
+    ///
+    /// Frames that cannot be decoded are logged and leave the state untouched.
+    pub fn apply_frame(&mut self, frame: &ProtocolFrame) -> StateUpdate {
+        match SportMessage::try_from(frame) {
+            Ok(message) => self.apply_message(&message),
+            Err(e) => {
+                warn!("{}", e);
+                StateUpdate {
+                    error: Some(e),
+                    ..StateUpdate::default()
+                }
+            }
+        }
+    }
+
+    /// Apply a decoded message of any sport to the state, then update the
+    /// game phase.
+    ///
+    /// A message of another sport than the current one closes the game, as
+    /// a new match bit would.
+    pub fn apply_message(&mut self, message: &SportMessage) -> StateUpdate {
+        let mut sport_changed = false;
+        let mut finished_game = None;
+        if let Some(sport) = message.sport() {
+            if self.sport != Some(sport) {
+                if self.sport.is_some() {
+                    info!("Sport changed to {:?}: closed the previous game", sport);
+                    let archive = self.start_new_game();
+                    self.event_log.push(archive.new_game_event());
+                    finished_game = Some(archive);
+                }
+                self.sport = Some(sport);
+                sport_changed = true;
+            }
+        }
+
+        let mut update = match message {
+            SportMessage::Basketball(message) => self.update_basketball(message),
+            SportMessage::Handball(message) => self.update_handball(message),
+            SportMessage::Futsal(message) => self.update_futsal(message),
+            SportMessage::Volleyball(message) => self.update_volleyball(message),
+        };
+        update.changed |= sport_changed;
+        update.changed |= self.update_phase(&mut update.events);
+        if let Some(archive) = finished_game {
+            update.events.insert(0, archive.new_game_event());
+            update.finished_game = Some(archive);
+        }
+        update
+    }
+
+    /// Apply a decoded basketball message to the state, then update the
+    /// game phase, like `apply_message`.
+    pub fn apply(&mut self, message: &BodetMessage) -> StateUpdate {
+        self.apply_message(&SportMessage::Basketball(message.clone()))
+    }
+
+    /// Apply a decoded handball message to the state, then update the game
+    /// phase, like `apply_message`.
+    pub fn apply_handball(&mut self, message: &HandballMessage) -> StateUpdate {
+        self.apply_message(&SportMessage::Handball(message.clone()))
+    }
+
+    /// Apply a decoded futsal message to the state, then update the game
+    /// phase, like `apply_message`.
+    pub fn apply_futsal(&mut self, message: &FutsalMessage) -> StateUpdate {
+        self.apply_message(&SportMessage::Futsal(message.clone()))
+    }
+
+    /// Apply a decoded volleyball message to the state, then update the
+    /// game phase, like `apply_message`.
+    pub fn apply_volleyball(&mut self, message: &VolleyballMessage) -> StateUpdate {
+        self.apply_message(&SportMessage::Volleyball(message.clone()))
+    }
+
+    /// Update the state from a basketball message, leaving the phase to
+    /// `apply_message`.
+    fn update_basketball(&mut self, message: &BodetMessage) -> StateUpdate {
+        let mut state_changed = false;
+        let mut events = Vec::new();
+        let mut finished_game = None;
+
+        match message {
+            BodetMessage::Message18(message) => {
+                info!("Received Message Type 18 (Game Time and Time-outs)");
+
+                let status_word = StatusWord18::from_byte(message.status_word);
+
+                // The new match bit going high closes the game. A state that
+                // hasn't seen a message 18 yet has nothing to archive.
+                if status_word.new_match && self.new_match == Some(false) {
+                    let archive = self.start_new_game();
+                    info!(
+                        "New match: closed the game at {} - {}",
+                        archive.state.display.home_score, archive.state.display.away_score
+                    );
+                    events.push(archive.new_game_event());
+                    finished_game = Some(archive);
+                }
+                self.new_match = Some(status_word.new_match);
+
+                info!(
+                    "Status Word - Clock Type: {}, Game Clock Off: {}, Horn On: {}, Time in Tenths: {}, New Match: {}, B7: {}",
+                    status_word.clock_type,
+                    status_word.game_clock_off,
+                    status_word.horn_on,
+                    status_word.time_in_tenths,
+                    status_word.new_match,
+                    status_word.b7
+                );
+
+                if status_word.game_clock_off {
+                    info!("Game Clock is OFF");
+                    self.game_state = "paused".to_string();
+                } else {
+                    info!("Game Clock is ON");
+                    self.game_state = "running".to_string();
+                }
+
+                let clock_type = if status_word.clock_type { ClockType::Rest } else { ClockType::Game };
+                let tenths = if status_word.time_in_tenths {
+                    // SS D t
+                    ascii_number(&[message.minutes_1, message.minutes_2])
+                        .zip(ascii_number(&[message.seconds_2]))
+                        .map(|(seconds, tenths)| seconds as u32 * 10 + tenths as u32)
+                } else {
+                    ascii_number(&[message.minutes_1, message.minutes_2])
+                        .or(Some(0))
+                        .zip(ascii_number(&[message.seconds_1, message.seconds_2]))
+                        .map(|(minutes, seconds)| (minutes as u32 * 60 + seconds as u32) * 10)
+                };
+                self.update_clock(clock_type, tenths, status_word.time_in_tenths);
+                info!("{:?} clock: {}", clock_type, self.display.time);
+
+                info!(
+                    "Home Time-outs: {}, Guest Time-outs: {}, Period: {}",
+                    message.home_time_outs as char,
+                    message.guest_time_outs as char,
+                    message.period as char
+                );
+
+                // Bit 4 of the status word only says the clock is in tenths;
+                // the protocol has no possession arrow.
+
+                self.update_timeouts(message.home_time_outs, message.guest_time_outs);
+                let sport = Sport::from_basketball_sports_id(message.sports_id).unwrap_or(Sport::Basketball);
+                self.update_period(sport, message.period, None);
+
+                self.update_horn(Horn::Game, status_word.horn_on, &mut events);
+
+                state_changed = true;
+            }
+
+            BodetMessage::Message19(message) => {
+                // info!("Received Message Type 19 (Time-out Clock)");
+
+                state_changed = self.update_timeout(
+                    message.home_time_out_indicator,
+                    message.guest_time_out_indicator,
+                    [message.seconds_1, message.seconds_2],
+                );
+            }
+
+            BodetMessage::Message20(message) => {
+                // info!("Received Message Type 20 (Time of Day)");
+
+                let time_of_day = Some(format!(
+                    "{}{}:{}{}",
+                    message.hours_1 as char, message.hours_2 as char, message.minutes_1 as char, message.minutes_2 as char
+                ));
+                if self.time_of_day != time_of_day {
+                    self.time_of_day = time_of_day;
+                    state_changed = true;
+                }
+            }
+
+            BodetMessage::Message30(message) => {
+                info!("Received Message Type 30 (Scores)");
+
+                info!(
+                    "Home Score: {}{}{}, Guest Score: {}{}{}",
+                    message.home_score_1 as char,
+                    message.home_score_2 as char,
+                    message.home_score_3 as char,
+                    message.guest_score_1 as char,
+                    message.guest_score_2 as char,
+                    message.guest_score_3 as char
+                );
+
+                self.update_scores(
+                    &[message.home_score_1, message.home_score_2, message.home_score_3],
+                    &[message.guest_score_1, message.guest_score_2, message.guest_score_3],
+                );
+                state_changed = true;
+            }
+
+            BodetMessage::Message31(message) => {
+                info!("Received Message Type 31 (Fouls and Player Info)");
+
+                info!(
+                    "Home Fouls: {}, Guest Fouls: {}, Player on Line 1: {}, Player on Line 2: {}, Faults of Player: {}, Team of Player: {}",
+                    message.home_fouls as char,
+                    message.guest_fouls as char,
+                    message.number_player_on_line_1 as char,
+                    message.number_player_on_line_2 as char,
+                    message.number_of_faults_of_player as char,
+                    message.team_of_player as char
+                );
+
+                self.home_fouls = digit_value(message.home_fouls);
+                self.away_fouls = digit_value(message.guest_fouls);
+                self.display.home_fouls = (message.home_fouls as char).to_string();
+                self.display.away_fouls = (message.guest_fouls as char).to_string();
+                state_changed = true;
+
+                // The player line is blanked about 10 s after the foul call
+                let number = ascii_text(&[message.number_player_on_line_1, message.number_player_on_line_2]);
+                let fouls = ascii_number(&[message.number_of_faults_of_player]);
+                if let (Some(number), Some(fouls), Some(team)) = (number, fouls, Team::from_byte(message.team_of_player)) {
+                    let fouls = fouls as u8;
+                    let period = self.display.period_name.clone();
+                    let time = self.display.time.clone();
+                    if let Some(record) = self.player_fouls.record(team, &number, fouls, &period, &time) {
+                        info!("Foul call: {:?} #{} now has {} personal fouls at {} {}", team, number, fouls, period, time);
+                        if record.fouled_out {
+                            info!("{:?} #{} fouled out", team, number);
+                            events.push(GameEvent::FouledOut(record.clone()));
+                        }
+
+                        let players = match team {
+                            Team::Home => &mut self.home_players,
+                            Team::Away => &mut self.away_players,
+                        };
+                        player_by_number(players, &number).fouls = Some(fouls);
+                    }
+                }
+            }
+
+            BodetMessage::Message50(message) => {
+                info!("Received Message Type 50 (Shot Clock)");
+
+                let status_word = StatusWord50::from_byte(message.status_word);
+
+                if status_word.possession_timer_in_tenths {
+                    info!(
+                        "Shot Clock Time: {}.{}",
+                        message.seconds_1 as char, message.seconds_2 as char
+                    );
+                    self.display.shot_clock = Some(format!("{}.{}", message.seconds_1 as char, message.seconds_2 as char));
+                    // S t
+                    self.shot_clock_tenths = ascii_number(&[message.seconds_1, message.seconds_2]).map(u32::from);
+                } else {
+                    info!(
+                        "Shot Clock Time: {}{}",
+                        message.seconds_1 as char, message.seconds_2 as char
+                    );
+                    self.display.shot_clock = Some(format!("{}{}", message.seconds_1 as char, message.seconds_2 as char));
+                    self.shot_clock_tenths = ascii_number(&[message.seconds_1, message.seconds_2]).map(|seconds| seconds as u32 * 10);
+                }
+
+                let shot_clock_state = if status_word.status_of_shot_clock {
+                    ShotClockState::Blank
+                } else if status_word.status_possession_timer {
+                    ShotClockState::Stopped
+                } else {
+                    ShotClockState::Running
+                };
+                if self.shot_clock_state != Some(shot_clock_state) {
+                    info!("Shot Clock is {:?}", shot_clock_state);
+                    self.shot_clock_state = Some(shot_clock_state);
+                }
+
+                self.update_horn(Horn::ShotClock, status_word.status_possession_horn, &mut events);
+
+                state_changed = true;
+            }
+
+            BodetMessage::Message36(message) => {
+                // info!("Received Message Type 36 (Possession Time Last Minute)");
+
+                let tenths = ascii_number(&[message.seconds_1, message.seconds_2, message.seconds_3]).map(u32::from);
+                self.update_clock(ClockType::Game, tenths, true);
+                state_changed = true;
+            }
+
+            BodetMessage::Message32(message) => {
+                info!("Received Message Type 32 (Personal Foul Indicator)");
+
+                let line = ascii_number(&[message.line_of_player_1, message.line_of_player_2]);
+                let fouls = foul_indicator_count(message.foul_indicator);
+                info!(
+                    "Team of Player: {}, Line: {:?}, Fouls: {}",
+                    message.team_of_player as char, line, fouls
+                );
+
+                match (Team::from_byte(message.team_of_player), line) {
+                    (Some(team), Some(line)) if (1..=12).contains(&line) => {
+                        let players = match team {
+                            Team::Home => &mut self.home_players,
+                            Team::Away => &mut self.away_players,
+                        };
+                        state_changed = apply_line_foul(players, line as u8, fouls);
+                    }
+                    (None, None) if message.team_of_player == 0x20 => {
+                        // Initialisation: the personal foul panels were erased
+                        for player in self
+                            .home_players
+                            .iter_mut()
+                            .chain(self.away_players.iter_mut())
+                            .filter(|p| p.line.is_some())
+                        {
+                            player.fouls = Some(0);
+                        }
+                        state_changed = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            BodetMessage::Message33(message) => {
+                // info!("Received Message Type 33 (Home Personal Fouls)");
+
+                state_changed = apply_line_fouls(&mut self.home_players, &message.foul_indicators);
+            }
+
+            BodetMessage::Message34(message) => {
+                // info!("Received Message Type 34 (Guest Personal Fouls)");
+
+                state_changed = apply_line_fouls(&mut self.away_players, &message.foul_indicators);
+            }
+
+            BodetMessage::Message37(message) => {
+                info!("Received Message Type 37 (Home Roster)");
+
+                let roster = Roster::from_bytes(&message.player_numbers);
+                info!(
+                    "Home Roster: {:?}",
+                    roster.players.iter().map(|p| p.number.as_str()).collect::<Vec<_>>()
+                );
+
+                let players_changed = apply_roster(&mut self.home_players, &roster);
+                state_changed = players_changed || self.home_roster != roster;
+                self.home_roster = roster;
+            }
+
+            BodetMessage::Message38(message) => {
+                info!("Received Message Type 38 (Guest Roster)");
+
+                let roster = Roster::from_bytes(&message.player_numbers);
+                info!(
+                    "Guest Roster: {:?}",
+                    roster.players.iter().map(|p| p.number.as_str()).collect::<Vec<_>>()
+                );
+
+                let players_changed = apply_roster(&mut self.away_players, &roster);
+                state_changed = players_changed || self.away_roster != roster;
+                self.away_roster = roster;
+            }
+
+            BodetMessage::Message56(message) => {
+                info!("Received Message Type 56 (Player Points)");
+
+                let number = ascii_text(&[message.player_number_1, message.player_number_2]);
+                // A blank total means the player hasn't scored yet
+                let points = ascii_number(&[message.total_score_1, message.total_score_2]).unwrap_or(0);
+
+                info!(
+                    "Team of Player: {}, Player: {:?}, Points: {}",
+                    message.team_of_player as char, number, points
+                );
+
+                state_changed = match (message.team_of_player, number) {
+                    (0x31, Some(number)) => apply_player_points(&mut self.home_players, &number, points),
+                    (0x32, Some(number)) => apply_player_points(&mut self.away_players, &number, points),
+                    (0x20, _) => {
+                        // Initialisation: the scorepad cleared every player's points
+                        for player in self
+                            .home_players
+                            .iter_mut()
+                            .chain(self.away_players.iter_mut())
+                        {
+                            player.points = None;
+                        }
+                        true
+                    }
+                    _ => false,
+                };
+            }
+
+            BodetMessage::Message60(message) => {
+                // info!("Received Message Type 60 (Team Status)");
+
+                let home_bonus = (message.home_status & (1 << 0)) != 0;
+                let away_bonus = (message.guest_status & (1 << 0)) != 0;
+                if self.home_bonus != home_bonus || self.away_bonus != away_bonus {
+                    info!("Team Foul Indicator - Home: {}, Guest: {}", home_bonus, away_bonus);
+                    self.home_bonus = home_bonus;
+                    self.away_bonus = away_bonus;
+                    state_changed = true;
+                }
+            }
+
+            BodetMessage::Message98(message) => {
+                info!("Received Message Type 98 (Home Team Name)");
+
+                let name = ascii_text(&message.name);
+                info!("Home Team Name: {:?}, Trigram: {:?}", name, ascii_text(&message.trigram));
+
+                if self.home_name != name {
+                    self.home_name = name;
+                    state_changed = true;
+                }
+            }
+
+            BodetMessage::Message99(message) => {
+                info!("Received Message Type 99 (Guest Team Name)");
+
+                let name = ascii_text(&message.name);
+                info!("Guest Team Name: {:?}, Trigram: {:?}", name, ascii_text(&message.trigram));
+
+                if self.away_name != name {
+                    self.away_name = name;
+                    state_changed = true;
+                }
+            }
+        }
+
+        self.event_log.extend(events.iter().cloned());
+
+        StateUpdate {
+            changed: state_changed,
+            events,
+            finished_game,
+            error: None,
+        }
+    }
+}
+
+impl GameState {
+    /// Update the state from a handball message.
+    fn update_handball(&mut self, message: &HandballMessage) -> StateUpdate {
+        let mut state_changed = false;
+        let mut events = Vec::new();
+
+        match message {
+            HandballMessage::Message01(message) => {
+                // info!("Received Handball Message Type 01 (Game Time and Time-outs)");
+
+                let status_word = StatusWord18::from_byte(message.status_word);
+                let clock_type = if status_word.clock_type { ClockType::Rest } else { ClockType::Game };
+                self.update_game_clock(
+                    status_word.game_clock_off,
+                    clock_type,
+                    [message.minutes_1, message.minutes_2],
+                    [message.seconds_1, message.seconds_2],
+                    None,
+                );
+
+                self.update_timeouts(message.home_time_outs, message.guest_time_outs);
+                self.update_period(Sport::Handball, message.period, None);
+                self.update_horn(Horn::Game, status_word.horn_on, &mut events);
+                state_changed = true;
+            }
+
+            HandballMessage::Message02(message) => {
+                info!("Received Handball Message Type 02 (Scores)");
+
+                self.update_scores(
+                    &[message.home_score_1, message.home_score_2, message.home_score_3],
+                    &[message.guest_score_1, message.guest_score_2, message.guest_score_3],
+                );
+                state_changed = true;
+            }
+
+            HandballMessage::Message03(message) => {
+                // info!("Received Handball Message Type 03 (Suspensions)");
+
+                let home = suspensions(&message.home_penalties, &message.home_player_numbers);
+                let away = suspensions(&message.guest_penalties, &message.guest_player_numbers);
+                if self.home_suspensions != home || self.away_suspensions != away {
+                    self.home_suspensions = home;
+                    self.away_suspensions = away;
+                    state_changed = true;
+                }
+            }
+
+            HandballMessage::Message04(message) => {
+                // info!("Received Handball Message Type 04 (Time-out Clock)");
+
+                state_changed = self.update_timeout(
+                    message.home_time_out_indicator,
+                    message.guest_time_out_indicator,
+                    [message.seconds_1, message.seconds_2],
+                );
+            }
+        }
+
+        self.event_log.extend(events.iter().cloned());
+
+        StateUpdate {
+            changed: state_changed,
+            events,
+            finished_game: None,
+            error: None,
+        }
+    }
+
+    /// Update the state from a futsal message.
+    fn update_futsal(&mut self, message: &FutsalMessage) -> StateUpdate {
+        let mut events = Vec::new();
+
+        match message {
+            FutsalMessage::Message45(message) => {
+                // info!("Received Futsal Message Type 45 (Game Time and Scores)");
+
+                let status_word = StatusWord18::from_byte(message.status_word);
+                let clock_type = if status_word.clock_type { ClockType::Rest } else { ClockType::Game };
+                self.update_game_clock(
+                    status_word.game_clock_off,
+                    clock_type,
+                    [message.minutes_1, message.minutes_2],
+                    [message.seconds_1, message.seconds_2],
+                    Some(message.tenths),
+                );
+
+                self.update_scores(
+                    &[message.home_score_1, message.home_score_2, message.home_score_3],
+                    &[message.guest_score_1, message.guest_score_2, message.guest_score_3],
+                );
+
+                self.update_period(Sport::Futsal, message.period, digit_value(message.extra_time));
+                self.update_horn(Horn::Game, status_word.horn_on, &mut events);
+            }
+        }
+
+        self.event_log.extend(events.iter().cloned());
+
+        StateUpdate {
+            changed: true,
+            events,
+            finished_game: None,
+            error: None,
+        }
+    }
+
+    /// Update the state from a volleyball message.
+    fn update_volleyball(&mut self, message: &VolleyballMessage) -> StateUpdate {
+        let mut state_changed = false;
+        let mut events = Vec::new();
+
+        let status_word = match message {
+            VolleyballMessage::Message06(message) => message.status_word,
+            VolleyballMessage::Message07(message) => message.status_word,
+            VolleyballMessage::Message08(message) => message.status_word,
+        };
+        let serving = Some(if StatusWordVolleyball::from_byte(status_word).home_serving { Team::Home } else { Team::Away });
+        if self.serving != serving {
+            self.serving = serving;
+            state_changed = true;
+        }
+
+        match message {
+            VolleyballMessage::Message06(message) => {
+                // info!("Received Volleyball Message Type 06 (Sets, Score and Time-outs)");
+
+                self.update_period(Sport::Volleyball, message.set, None);
+                self.home_sets = ascii_number(&[message.home_sets]).map(|sets| sets as u8);
+                self.away_sets = ascii_number(&[message.guest_sets]).map(|sets| sets as u8);
+                self.update_scores(
+                    &[message.home_score_1, message.home_score_2],
+                    &[message.guest_score_1, message.guest_score_2],
+                );
+                self.update_timeout(
+                    message.home_time_outs,
+                    message.guest_time_outs,
+                    [message.seconds_1, message.seconds_2],
+                );
+                self.update_timeouts(message.home_time_outs, message.guest_time_outs);
+                state_changed = true;
+            }
+
+            VolleyballMessage::Message07(message) => {
+                // info!("Received Volleyball Message Type 07 (Game Time)");
+
+                let status_word = StatusWordVolleyball::from_byte(message.status_word);
+                self.update_game_clock(
+                    status_word.game_clock_off,
+                    ClockType::Game,
+                    [message.minutes_1, message.minutes_2],
+                    [message.seconds_1, message.seconds_2],
+                    None,
+                );
+                self.update_horn(Horn::Game, status_word.horn_on, &mut events);
+                state_changed = true;
+            }
+
+            VolleyballMessage::Message08(message) => {
+                // info!("Received Volleyball Message Type 08 (Set Scores)");
+
+                // Only ended sets are sent, the others are blank
+                let set_scores: Vec<SetScore> = message
+                    .set_scores
+                    .chunks(4)
+                    .map_while(|set| {
+                        Some(SetScore {
+                            home: ascii_number(&set[..2])?,
+                            away: ascii_number(&set[2..])?,
+                        })
+                    })
+                    .collect();
+                if self.set_scores != set_scores {
+                    self.set_scores = set_scores;
+                    state_changed = true;
+                }
+            }
+        }
+
+        self.event_log.extend(events.iter().cloned());
+
+        StateUpdate {
+            changed: state_changed,
+            events,
+            finished_game: None,
+            error: None,
+        }
+    }
+}
+
+/// Show a clock value: "M:SS", or "S.t" under a minute when it is known to
+/// the tenth. "--:--" when the scorepad sent no readable value.
+pub fn format_clock(tenths: Option<u32>, in_tenths: bool) -> String {
+    match tenths {
+        Some(tenths) if in_tenths && tenths < 600 => format!("{}.{}", tenths / 10, tenths % 10),
+        Some(tenths) => format!("{}:{:02}", tenths / 600, tenths / 10 % 60),
+        None => "--:--".to_string(),
+    }
+}
+
+/// Value of a single digit byte, `None` when blank.
+fn digit_value(byte: u8) -> Option<u8> {
+    ascii_number(&[byte]).map(|value| value as u8)
+}
+
+/// Decode the running suspensions of one team from handball message 03.
+/// Slots showing no time (blank or 0:00) are free.
+fn suspensions(penalties: &[u8], numbers: &[u8]) -> Vec<Suspension> {
+    penalties
+        .chunks(3)
+        .zip(numbers.chunks(2))
+        .take(SUSPENSION_SLOTS)
+        .filter(|(time, _)| ascii_number(&time[..1]).unwrap_or(0) + ascii_number(&time[1..]).unwrap_or(0) > 0)
+        .map(|(time, number)| Suspension {
+            number: ascii_text(number),
+            time: format!("{}:{}{}", time[0] as char, time[1] as char, time[2] as char),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basketball_parser::{Message18, Message30, Message50};
+    use crate::frame::{ProtocolFrame, CTRL_DATA, DEFAULT_ADDRESS};
+
+    const RUNNING: StatusWord18 = StatusWord18 {
+        clock_type: false,
+        game_clock_off: false,
+        horn_on: false,
+        time_in_tenths: false,
+        new_match: false,
+        b7: true,
+    };
+    const STOPPED: StatusWord18 = StatusWord18 { game_clock_off: true, ..RUNNING };
+    const REST: StatusWord18 = StatusWord18 { clock_type: true, ..RUNNING };
+    const HORN: StatusWord18 = StatusWord18 { horn_on: true, ..STOPPED };
+
+    fn send(state: &mut GameState, message: BodetMessage) -> StateUpdate {
+        state.apply_message(&SportMessage::Basketball(message))
+    }
+
+    fn clock(state: &mut GameState, status_word: StatusWord18, minutes: u8, seconds: u8, period: u8) -> StateUpdate {
+        send(state, BodetMessage::Message18(Message18::new(status_word, minutes, seconds, 0, 0, period)))
+    }
+
+    fn score(state: &mut GameState, home: u16, away: u16) {
+        send(state, BodetMessage::Message30(Message30::new(home, away)));
+    }
+
+    fn phase_changes(update: &StateUpdate) -> Vec<(GamePhase, GamePhase)> {
+        update
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::Phase(change) => Some((change.from, change.to)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// A state in the last second of the fourth quarter at `home` - `away`.
+    fn last_second(home: u16, away: u16) -> GameState {
+        let mut state = GameState::default();
+        score(&mut state, home, away);
+        clock(&mut state, RUNNING, 0, 2, b'4');
+        clock(&mut state, RUNNING, 0, 1, b'4');
+        state
+    }
+
+    #[test]
+    fn game_clock_starts_the_first_period() {
+        let mut state = GameState::default();
+        let update = clock(&mut state, STOPPED, 10, 0, b'1');
+        assert_eq!(state.phase, GamePhase::PreGame);
+        assert!(phase_changes(&update).is_empty());
+
+        let update = clock(&mut state, RUNNING, 9, 59, b'1');
+        assert_eq!(phase_changes(&update), [(GamePhase::PreGame, GamePhase::InPeriod)]);
+        assert_eq!(state.phase, GamePhase::InPeriod);
+    }
+
+    #[test]
+    fn apply_updates_the_phase_too() {
+        let mut state = GameState::default();
+        let update = state.apply(&BodetMessage::Message18(Message18::new(RUNNING, 9, 59, 0, 0, b'1')));
+        assert_eq!(phase_changes(&update), [(GamePhase::PreGame, GamePhase::InPeriod)]);
+    }
+
+    #[test]
+    fn tied_at_the_end_of_regulation_is_not_final() {
+        let mut state = last_second(80, 80);
+        let update = clock(&mut state, RUNNING, 0, 0, b'4');
+        assert_eq!(phase_changes(&update), [(GamePhase::InPeriod, GamePhase::EndOfPeriod)]);
+
+        clock(&mut state, REST, 2, 0, b'O');
+        assert_eq!(state.phase, GamePhase::EndOfPeriod);
+        assert_eq!(state.display.period_name, "Break");
+
+        let update = clock(&mut state, RUNNING, 4, 59, b'O');
+        assert_eq!(phase_changes(&update), [(GamePhase::EndOfPeriod, GamePhase::Overtime)]);
+        assert_eq!(state.display.period_name, "OT1");
+    }
+
+    #[test]
+    fn winner_at_the_end_of_regulation_is_final() {
+        let mut state = last_second(80, 78);
+        let update = clock(&mut state, RUNNING, 0, 0, b'4');
+        assert_eq!(phase_changes(&update), [(GamePhase::InPeriod, GamePhase::Final)]);
+
+        // Nothing moves a final game but a new match
+        clock(&mut state, STOPPED, 0, 0, b'4');
+        assert_eq!(state.phase, GamePhase::Final);
+    }
+
+    #[test]
+    fn horn_at_zero_ends_the_period() {
+        let mut state = GameState::default();
+        clock(&mut state, RUNNING, 0, 1, b'1');
+        let update = clock(&mut state, HORN, 0, 0, b'1');
+        assert_eq!(state.phase, GamePhase::EndOfPeriod);
+        assert!(update.events.iter().any(|event| matches!(
+            event,
+            GameEvent::Horn(HornEvent { horn: Horn::Game, on: true, .. })
+        )));
+
+        let update = clock(&mut state, STOPPED, 0, 0, b'1');
+        assert!(update.events.iter().any(|event| matches!(
+            event,
+            GameEvent::Horn(HornEvent { horn: Horn::Game, on: false, .. })
+        )));
+    }
+
+    #[test]
+    fn horn_on_a_count_up_clock_ends_the_period() {
+        let mut state = GameState::default();
+        clock(&mut state, RUNNING, 19, 58, b'1');
+        clock(&mut state, RUNNING, 19, 59, b'1');
+        assert_eq!(state.clock_direction, Some(ClockDirection::Up));
+        assert_eq!(state.phase, GamePhase::InPeriod);
+
+        clock(&mut state, HORN, 20, 0, b'1');
+        assert_eq!(state.phase, GamePhase::EndOfPeriod);
+    }
+
+    #[test]
+    fn shot_clock_horn_leaves_the_phase() {
+        let mut state = GameState::default();
+        clock(&mut state, RUNNING, 5, 0, b'2');
+        let horn = StatusWord50 { b7: true, status_possession_horn: true, ..StatusWord50::default() };
+        let update = send(&mut state, BodetMessage::Message50(Message50::new(horn, 0)));
+        assert!(update.events.iter().any(|event| matches!(
+            event,
+            GameEvent::Horn(HornEvent { horn: Horn::ShotClock, on: true, .. })
+        )));
+        assert!(phase_changes(&update).is_empty());
+        assert_eq!(state.phase, GamePhase::InPeriod);
+    }
+
+    /// Message 31 showing a foul call: `fouls` personal fouls for shirt
+    /// `number` of the home team.
+    fn foul_call(state: &mut GameState, number: &str, fouls: u8) -> StateUpdate {
+        let text = format!("315 0 0{:>2}{}1", number, fouls);
+        let frame = ProtocolFrame::new(DEFAULT_ADDRESS, CTRL_DATA, text.into_bytes());
+        send(state, BodetMessage::try_from(&frame).unwrap())
+    }
+
+    fn fouled_out(update: &StateUpdate) -> Vec<&FoulRecord> {
+        update
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::FouledOut(record) => Some(record),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repeated_foul_call_is_counted_once() {
+        let mut state = GameState::default();
+        for _ in 0..10 {
+            foul_call(&mut state, "7", 2);
+        }
+        assert_eq!(state.player_fouls.records.len(), 1);
+        assert_eq!(state.player_fouls.fouls_of(Team::Home, "7"), Some(2));
+        assert_eq!(state.home_players[0].fouls, Some(2));
+
+        foul_call(&mut state, "7", 3);
+        foul_call(&mut state, "12", 3);
+        assert_eq!(state.player_fouls.records.len(), 3);
+    }
+
+    #[test]
+    fn player_fouls_out_at_five() {
+        let mut state = GameState::default();
+        for fouls in 1..=4 {
+            assert!(fouled_out(&foul_call(&mut state, "7", fouls)).is_empty());
+        }
+        let update = foul_call(&mut state, "7", 5);
+        let records = fouled_out(&update);
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].team, records[0].number.as_str(), records[0].fouls), (Team::Home, "7", 5));
+        assert!(state.player_fouls.is_fouled_out(Team::Home, "7"));
+
+        // The call stays on the scorepad for a while
+        assert!(fouled_out(&foul_call(&mut state, "7", 5)).is_empty());
+    }
+
+    #[test]
+    fn player_fouls_out_at_a_configured_six() {
+        let mut state = GameState {
+            player_fouls: FoulLedger::new(6),
+            ..GameState::default()
+        };
+        for fouls in 1..=5 {
+            assert!(fouled_out(&foul_call(&mut state, "7", fouls)).is_empty());
+        }
+        assert!(!state.player_fouls.is_fouled_out(Team::Home, "7"));
+        assert_eq!(fouled_out(&foul_call(&mut state, "7", 6)).len(), 1);
+        assert!(state.player_fouls.is_fouled_out(Team::Home, "7"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Handball messages of the Bodet Scorepad protocol.
+//!
+//! Same conventions as `basketball_parser`: each `MessageNN` struct mirrors
+//! the byte layout of message type NN and holds the raw ASCII bytes, and
+//! `GameState::apply_handball` updates the state from it.
+
+use crate::basketball_parser::{check_digits, check_len, check_type_len, check_sports_id};
+use crate::error::ParseError;
+use crate::frame::ProtocolFrame;
+
+/// Sports id byte of handball messages.
+pub const SPORTS_ID_HANDBALL: u8 = b'4';
+
+/// Number of 2-minute suspensions shown per team.
+pub const SUSPENSION_SLOTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message01 {
+    pub id_1: u8,            // First byte of message ID
+    pub id_2: u8,            // Second byte of message ID
+    pub status_word: u8,     // Status word byte, b0-b2 as in basketball message 18
+    pub sports_id: u8,       // This needs to be 4 for handball
+    pub minutes_1: u8,       // Minutes * 10
+    pub minutes_2: u8,       // Minutes * 1
+    pub seconds_1: u8,       // Seconds * 10
+    pub seconds_2: u8,       // Seconds * 1
+    pub home_time_outs: u8,  // Home time-outs
+    pub guest_time_outs: u8, // Guest time-outs
+    pub period: u8,          // Period number, 'E' during extra time
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message02 {
+    pub id_1: u8,               // First byte of message ID
+    pub id_2: u8,               // Second byte of message ID
+    pub sports_id: u8,          // This needs to be 4 for handball
+    pub home_score_1: u8,       // Home score position 1
+    pub home_score_2: u8,       // Home score position 2
+    pub home_score_3: u8,       // Home score position 3
+    pub guest_score_1: u8,      // Guest score position 1
+    pub guest_score_2: u8,      // Guest score position 2
+    pub guest_score_3: u8,      // Guest score position 3
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message03 {
+    pub id_1: u8,                    // First byte of message ID
+    pub id_2: u8,                    // Second byte of message ID
+    pub sports_id: u8,               // This needs to be 4 for handball
+    pub home_penalties: Vec<u8>,     // Home suspension times, M SS per slot (3 slots)
+    pub guest_penalties: Vec<u8>,    // Guest suspension times, M SS per slot (3 slots)
+    pub home_player_numbers: Vec<u8>, // Home suspended players, 2 characters per slot
+    pub guest_player_numbers: Vec<u8>, // Guest suspended players, 2 characters per slot
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message04 {
+    pub id_1: u8,                   // First byte of message ID
+    pub id_2: u8,                   // Second byte of message ID
+    pub sports_id: u8,              // This needs to be 4 for handball
+    pub home_time_out_indicator: u8, // Home time-outs, alternates with count - 1 during a time-out
+    pub guest_time_out_indicator: u8, // Guest time-outs, alternates with count - 1 during a time-out
+    pub seconds_1: u8,              // Time-out countdown seconds * 10
+    pub seconds_2: u8,              // Time-out countdown seconds * 1
+}
+
+/// A decoded handball message, one variant per message type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandballMessage {
+    /// Message 01: game clock, time-outs and period.
+    Message01(Message01),
+    /// Message 02: scores.
+    Message02(Message02),
+    /// Message 03: 2-minute suspensions.
+    Message03(Message03),
+    /// Message 04: time-out countdown.
+    Message04(Message04),
+}
+
+impl TryFrom<&ProtocolFrame> for HandballMessage {
+    type Error = ParseError;
+
+    fn try_from(frame: &ProtocolFrame) -> Result<Self, Self::Error> {
+        let message = &frame.message;
+
+        check_type_len(message)?;
+
+        match (message[0], message[1]) {
+            // Message Type 01
+            (0x30, 0x31) => {
+                check_len(1, message, 11)?;
+
+                let decoded = Message01 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    sports_id: message[3],
+                    minutes_1: message[4],
+                    minutes_2: message[5],
+                    seconds_1: message[6],
+                    seconds_2: message[7],
+                    home_time_outs: message[8],
+                    guest_time_outs: message[9],
+                    period: message[10],
+                };
+
+                check_sports_id(1, decoded.sports_id, &[SPORTS_ID_HANDBALL])?;
+                check_digits(
+                    1,
+                    &[
+                        ("minutes_1", decoded.minutes_1),
+                        ("minutes_2", decoded.minutes_2),
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                        ("home_time_outs", decoded.home_time_outs),
+                        ("guest_time_outs", decoded.guest_time_outs),
+                    ],
+                )?;
+
+                Ok(HandballMessage::Message01(decoded))
+            }
+
+            // Message Type 02
+            (0x30, 0x32) => {
+                check_len(2, message, 9)?;
+
+                let decoded = Message02 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    home_score_1: message[3],
+                    home_score_2: message[4],
+                    home_score_3: message[5],
+                    guest_score_1: message[6],
+                    guest_score_2: message[7],
+                    guest_score_3: message[8],
+                };
+
+                check_sports_id(2, decoded.sports_id, &[SPORTS_ID_HANDBALL])?;
+                check_digits(
+                    2,
+                    &[
+                        ("home_score_1", decoded.home_score_1),
+                        ("home_score_2", decoded.home_score_2),
+                        ("home_score_3", decoded.home_score_3),
+                        ("guest_score_1", decoded.guest_score_1),
+                        ("guest_score_2", decoded.guest_score_2),
+                        ("guest_score_3", decoded.guest_score_3),
+                    ],
+                )?;
+
+                Ok(HandballMessage::Message02(decoded))
+            }
+
+            // Message Type 03
+            (0x30, 0x33) => {
+                check_len(3, message, 33)?;
+
+                let decoded = Message03 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    home_penalties: message[3..12].to_vec(),
+                    guest_penalties: message[12..21].to_vec(),
+                    home_player_numbers: message[21..27].to_vec(),
+                    guest_player_numbers: message[27..33].to_vec(),
+                };
+
+                check_sports_id(3, decoded.sports_id, &[SPORTS_ID_HANDBALL])?;
+                for &byte in message[3..33].iter() {
+                    check_digits(3, &[("penalties", byte)])?;
+                }
+
+                Ok(HandballMessage::Message03(decoded))
+            }
+
+            // Message Type 04
+            (0x30, 0x34) => {
+                check_len(4, message, 7)?;
+
+                let decoded = Message04 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    sports_id: message[2],
+                    home_time_out_indicator: message[3],
+                    guest_time_out_indicator: message[4],
+                    seconds_1: message[5],
+                    seconds_2: message[6],
+                };
+
+                check_sports_id(4, decoded.sports_id, &[SPORTS_ID_HANDBALL])?;
+                check_digits(
+                    4,
+                    &[
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                    ],
+                )?;
+
+                Ok(HandballMessage::Message04(decoded))
+            }
+
+            _ => Err(ParseError::UnknownMessageType(message[0], message[1])),
+        }
+    }
+}
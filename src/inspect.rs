@@ -0,0 +1,101 @@
+//! Readable dump of a log: every frame, what it changes in the game state
+//! and the events it raises.
+//!
+//! A frame is shown on one line with the read it came from, its message
+//! type and its message as escaped ASCII, followed by the display values it
+//! changed, e.g.
+//!
+//! ```text
+//! read 1      18  "18\x805 15410  1 "  away_timeouts="0"  home_timeouts="1"  period_name="1 Quarter"  time="1:54"
+//!            event phase {"from":"pre-game","to":"in-period","period":"1 Quarter","time":"1:54"}
+//! read 2      18  "18\x805 15310  1 "  time="1:53"
+//! ```
+
+use std::io::{self, Write};
+
+use crate::frame::{FrameDecoder, DEFAULT_ADDRESS};
+use crate::game_state::GameState;
+use crate::session_log::Chunk;
+use crate::sport::SportMessage;
+
+/// What an inspection went through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub reads: usize,
+    pub frames: usize,
+    pub errors: usize, // broken frames and frames that couldn't be decoded
+}
+
+/// Decode `chunks`, the reads of a log, and apply them to `state`, writing
+/// every frame to `out`.
+pub fn inspect<W: Write>(chunks: &[Chunk], state: &mut GameState, out: &mut W) -> io::Result<Summary> {
+    let mut summary = Summary {
+        reads: chunks.len(),
+        ..Summary::default()
+    };
+    let mut decoder = FrameDecoder::new();
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let read = format!("read {:<5}", index + 1);
+        decoder.push(&chunk.bytes);
+        while let Some(result) = decoder.next_frame() {
+            let frame = match result {
+                Ok(frame) => frame,
+                Err(e) => {
+                    summary.errors += 1;
+                    writeln!(out, "{} error: {}", read, e)?;
+                    continue;
+                }
+            };
+            summary.frames += 1;
+
+            let mut line = format!("{} {:>3}  \"{}\"", read, message_type(&frame.message), frame.message.escape_ascii());
+            if frame.address != DEFAULT_ADDRESS {
+                line.push_str(&format!("  address=0x{:02X}", frame.address));
+            }
+
+            let message = match SportMessage::try_from(&frame) {
+                Ok(message) => message,
+                Err(e) => {
+                    summary.errors += 1;
+                    writeln!(out, "{}  error: {}", line, e)?;
+                    continue;
+                }
+            };
+
+            let before = serde_json::to_value(&state.display).unwrap_or_default();
+            let update = state.apply_message(&message);
+            let after = serde_json::to_value(&state.display).unwrap_or_default();
+            if let (Some(before), Some(after)) = (before.as_object(), after.as_object()) {
+                for (key, value) in after {
+                    if before.get(key) != Some(value) {
+                        line.push_str(&format!("  {}={}", key, value));
+                    }
+                }
+            }
+            writeln!(out, "{}", line)?;
+
+            for event in &update.events {
+                let data = serde_json::to_string(event).unwrap_or_default();
+                writeln!(out, "{:10} event {} {}", "", event.name(), data)?;
+            }
+        }
+    }
+
+    writeln!(
+        out,
+        "{} reads, {} frames, {} errors",
+        summary.reads, summary.frames, summary.errors
+    )?;
+    Ok(summary)
+}
+
+/// The message type of a message, its first two bytes, e.g. "18".
+fn message_type(message: &[u8]) -> String {
+    match message {
+        [id_1, id_2, ..] if id_1.is_ascii_digit() && id_2.is_ascii_digit() => {
+            format!("{}{}", *id_1 as char, *id_2 as char)
+        }
+        _ => "?".to_string(),
+    }
+}
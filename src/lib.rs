@@ -0,0 +1,60 @@
+//! Parser and live server for the Bodet Scorepad network protocol.
+//!
+//! The crate is split in layers that can be used on their own:
+//!
+//! - [`frame`]: the frame codec (`SOH address STX CTRL message ETX LRC`),
+//!   including a streaming decoder for TCP input.
+//! - [`basketball_parser`]: the typed basketball messages, decoded from a
+//!   frame with [`BodetMessage::try_from`](basketball_parser::BodetMessage).
+//! - [`sport`]: sport-aware decoding of the same frames, picking the
+//!   [`handball_parser`], [`futsal_parser`] or [`volleyball_parser`] layouts
+//!   over the basketball ones from the message type and sports id.
+//! - [`game_state`]: the typed state model, its display strings, and
+//!   [`GameState::apply_message`](game_state::GameState::apply_message), the
+//!   reducer that applies a message to it.
+//! - [`period`]: the period model, counting overtimes and breaks.
+//! - [`court`]: one game state per scoreboard, picked by frame address or
+//!   connection.
+//! - [`tcp_server`] and [`web_server`]: the ingest server and the overlay/API
+//!   server used by the `scoreboard-rust` binary, set up from a [`config`].
+//! - [`session_log`], [`inspect`], [`replay`] and [`simulator`]: reading,
+//!   decoding and replaying the logs of a session, and made-up games.
+//!
+//! Decoding a captured frame:
+//!
+//! ```
+//! use scoreboard_rust::basketball_parser::BodetMessage;
+//! use scoreboard_rust::frame::parse_raw_data;
+//! use scoreboard_rust::game_state::GameState;
+//!
+//! let bytes = [
+//!     0x01, 0x7F, 0x02, 0x47, 0x33, 0x30, 0x35, 0x20, 0x30, 0x20, 0x20, 0x30, 0x20, 0x03, 0x2F,
+//! ];
+//! let frame = parse_raw_data(&bytes).unwrap();
+//! let message = BodetMessage::try_from(&frame).unwrap();
+//! assert!(matches!(message, BodetMessage::Message30(_)));
+//!
+//! let mut state = GameState::default();
+//! let update = state.apply(&message);
+//! assert!(update.changed);
+//! assert_eq!(state.home_score, Some(0));
+//! assert_eq!(state.display.home_score, " 0 ");
+//! ```
+
+pub mod basketball_parser;
+pub mod config;
+pub mod court;
+pub mod error;
+pub mod frame;
+pub mod futsal_parser;
+pub mod game_state;
+pub mod handball_parser;
+pub mod inspect;
+pub mod period;
+pub mod replay;
+pub mod session_log;
+pub mod simulator;
+pub mod sport;
+pub mod tcp_server;
+pub mod volleyball_parser;
+pub mod web_server;
@@ -1,735 +1,561 @@
+use clap::{Args, Parser, Subcommand};
 use env_logger::Env;
-use log::{debug, error, info, warn};
-use std::{
-    fs::OpenOptions, io::{Read, Write}, net::{TcpListener, TcpStream}, os::linux::raw::stat, sync::{Arc, Mutex}, thread, time::{Duration, SystemTime, UNIX_EPOCH}
-};
-use warp::Filter;
-use serde::{Deserialize, Serialize};
-
-/// Protocol control characters.
-const SOH: u8 = 0x01;
-const STX: u8 = 0x02;
-const ETX: u8 = 0x03;
-
-/// Represents a parsed protocol frame.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ProtocolFrame {
-    pub soh: u8,          // should be 0x01
-    pub address: u8,      // included in LRC calculation
-    pub stx: u8,          // should be 0x02
-    pub ctrl: u8,         // included in LRC calculation
-    pub message: Vec<u8>, // variable-length payload
-    pub etx: u8,          // should be 0x03
-    pub lrc: u8,          // 1 byte checksum as transmitted
+use log::{error, info, warn};
+use std::{fs::File, io, net::IpAddr, path::PathBuf, process, sync::Arc, thread, time::Duration};
+
+use scoreboard_rust::config::Config;
+use scoreboard_rust::court::{CourtRouting, Courts};
+use scoreboard_rust::error::ConfigError;
+use scoreboard_rust::game_state::{FoulLedger, GameState};
+use scoreboard_rust::inspect::inspect;
+use scoreboard_rust::period::PeriodFormat;
+use scoreboard_rust::replay::{Pace, Replay, Target};
+use scoreboard_rust::session_log::{read_log, write_log, Chunk, LogFormat};
+use scoreboard_rust::simulator::Simulation;
+use scoreboard_rust::tcp_server;
+use scoreboard_rust::web_server::start_web_server;
+
+/// Bodet Scorepad ingest server and live scoreboard overlay.
+///
+/// Without a command, runs the server. Server settings come from the
+/// command line, then the config file, then the defaults.
+#[derive(Parser, Debug)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    server: ServerArgs,
 }
 
-impl ProtocolFrame {
-    /// Compute LRC for a byte slice using the protocol rule:
-    /// XOR all bytes, mask with 0x7F, then if < 32 add 32.
-    ///
-    /// This function implements the canonical transformation and can be
-    /// used for both constructing and validating frames.
-    pub fn compute_lrc_bytes(bytes: &[u8]) -> u8 {
-        let mut xor: u8 = 0;
-        for &b in bytes {
-            xor ^= b;
-        }
-        let mut lrc = xor & 0x7F;
-        if lrc < 32 {
-            // use wrapping_add to be explicit about u8 arithmetic
-            lrc = lrc.wrapping_add(32);
-        }
-        lrc
-    }
-
-    /// Build the slice of bytes that are used for the LRC calculation:
-    /// Address, STX, CTRL, Message..., ETX (SOH excluded, ETX included).
-    fn lrc_input_bytes(&self) -> Vec<u8> {
-        let mut v = Vec::with_capacity(3 + self.message.len() + 1);
-        v.push(self.address);
-        v.push(self.stx);
-        v.push(self.ctrl);
-        v.extend_from_slice(&self.message);
-        v.push(self.etx);
-        v
-    }
-
-    /// Compute the expected LRC for this frame (based on current fields).
-    pub fn expected_lrc(&self) -> u8 {
-        let bytes = self.lrc_input_bytes();
-        Self::compute_lrc_bytes(&bytes)
-    }
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the scorepad ingest server and the overlay (the default)
+    Server(ServerArgs),
+    /// Play session logs with their original timing, to a server or in-process
+    Replay(ReplayArgs),
+    /// Decode a log into readable frames, state changes and events
+    Inspect(InspectArgs),
+    /// Play a made-up basketball game to a server, or write it as a log
+    Simulate(SimulateArgs),
+    /// Convert a log between the session, hex and raw formats
+    Convert(ConvertArgs),
+}
 
-    /// Validate the stored LRC against the computed value.
-    pub fn validate_lrc(&self) -> bool {
-        self.expected_lrc() == self.lrc
-    }
+/// The config file and the competition settings, shared by every command
+/// that keeps a game state.
+#[derive(Args, Debug)]
+struct GameArgs {
+    /// TOML config file
+    #[arg(short, long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Personal fouls a player fouls out at, e.g. 6 for NBA rules [default: 5]
+    #[arg(long, value_name = "FOULS")]
+    foul_limit: Option<u8>,
+
+    /// Basketball periods: quarters or halves [default: quarters]
+    #[arg(long, value_name = "FORMAT")]
+    period_format: Option<PeriodFormat>,
 }
 
-/// Parse raw byte data into a ProtocolFrame.
-fn parse_raw_data(data: &[u8]) -> Result<ProtocolFrame, String> {
-    if data.len() < 5 {
-        return Err("Data too short to be a valid frame".to_string());
-    }
+/// Settings of the web server and its courts, shared by the server and
+/// in-process replays.
+#[derive(Args, Debug)]
+struct WebArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Address to serve the overlay and the API on [default: 0.0.0.0]
+    #[arg(long, value_name = "IP")]
+    web_address: Option<IpAddr>,
+
+    /// Port to serve the overlay and the API on [default: 3030]
+    #[arg(long, value_name = "PORT")]
+    web_port: Option<u16>,
+
+    /// Directory holding overlay.html, overlay.css, overlay.js and media/
+    #[arg(long, value_name = "DIR")]
+    static_dir: Option<PathBuf>,
+
+    /// How frames are assigned to courts: single, address (one court per
+    /// frame address) or connection (one per scorepad IP) [default: single]
+    #[arg(long, value_name = "ROUTING")]
+    court_routing: Option<CourtRouting>,
+}
 
-    if data[0] != SOH {
-        return Err("Invalid SOH".to_string());
-    }
+#[derive(Args, Debug)]
+struct ServerArgs {
+    #[command(flatten)]
+    web: WebArgs,
 
-    if data[2] != STX {
-        return Err("Invalid STX".to_string());
-    }
+    /// Address to listen on for the scorepad [default: 0.0.0.0]
+    #[arg(long, value_name = "IP")]
+    tcp_address: Option<IpAddr>,
 
-    if data[data.len() - 2] != ETX {
-        return Err("Invalid ETX".to_string());
-    }
+    /// Port to listen on for the scorepad [default: 4001]
+    #[arg(long, value_name = "PORT")]
+    tcp_port: Option<u16>,
 
-    let soh = data[0];
-    let address = data[1];
-    let stx = data[2];
-    let ctrl = data[3];
-    let message = data[4..data.len() - 2].to_vec();
-    let etx = data[data.len() - 2];
-    let lrc = data[data.len() - 1];
-
-    let frame = ProtocolFrame {
-        soh,
-        address,
-        stx,
-        ctrl,
-        message,
-        etx,
-        lrc,
-    };
+    /// Directory of the session logs and archived games [default: data_log]
+    #[arg(long, value_name = "DIR")]
+    log_dir: Option<PathBuf>,
 
-    if !frame.validate_lrc() {
-        return Err("LRC validation failed".to_string());
-    }
+    /// off, error, warn, info, debug or trace; overrides RUST_LOG [default: info]
+    #[arg(long, value_name = "LEVEL")]
+    log_level: Option<String>,
 
-    Ok(frame)
+    /// Don't log TCP session bytes and finished games to files
+    #[arg(long)]
+    dev: bool,
 }
 
-#[derive(Debug)]
-struct Message18 {
-    id_1: u8,            // First byte of message ID
-    id_2: u8,            // Second byte of message ID
-    status_word: u8,     // Status word byte
-    sports_id: u8,       // This needs to be 5 for basketball
-    minutes_1: u8,       // Minutes * 10
-    minutes_2: u8,       // Minutes * 1
-    seconds_1: u8,       // Seconds * 10
-    seconds_2: u8,       // Seconds * 1
-    home_time_outs: u8,  // Home time-outs
-    guest_time_outs: u8, // Guest time-outs
-    byte_11: Option<u8>, // Reserved / unused
-    byte_12: Option<u8>, // Reserved / unused
-    period: u8,          // Current period
-    byte_14: Option<u8>, // Reserved / unused
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    /// Logs to send, one after the other
+    #[arg(required = true, value_name = "FILE")]
+    files: Vec<PathBuf>,
+
+    /// Server to send to
+    #[arg(long, value_name = "HOST:PORT", default_value = "127.0.0.1:4001")]
+    to: String,
+
+    /// Play into a web server of this process instead of sending to a server
+    #[arg(long, conflicts_with = "to")]
+    in_process: bool,
+
+    /// How many times faster than logged to play, e.g. 0.5 or 10
+    #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+    speed: f64,
+
+    /// Send one read each time Enter is pressed
+    #[arg(long, conflicts_with = "speed")]
+    step: bool,
+
+    /// Start over once the end is reached, until interrupted
+    #[arg(long = "loop")]
+    looping: bool,
+
+    /// Offset into the logs to start at, e.g. 90, 1:30 or 1:02:03.5;
+    /// earlier reads are sent at once
+    #[arg(long, value_name = "TIME", value_parser = parse_offset)]
+    start: Option<Duration>,
+
+    /// Offset into the logs to stop at
+    #[arg(long, value_name = "TIME", value_parser = parse_offset)]
+    end: Option<Duration>,
+
+    /// Time between two reads of logs without times, in milliseconds. Logs
+    /// written before the server recorded read times, and raw logs, have
+    /// none, so they can't be played with their original timing
+    #[arg(long, value_name = "MS", default_value_t = 100)]
+    interval: u64,
+
+    /// Format of the logs
+    #[arg(long, value_name = "FORMAT", default_value = "session")]
+    format: LogFormat,
+
+    #[command(flatten, next_help_heading = "Courts and web server with --in-process")]
+    web: WebArgs,
 }
 
-#[derive(Debug)]
-struct Message30 {
-    id_1: u8,               // First byte of message ID
-    id_2: u8,               // Second byte of message ID
-    sports_id: u8,          // This needs to be 5 for basketball
-    home_score_1: u8,       // Home score position 1
-    home_score_2: u8,       // Home score position 2
-    home_score_3: u8,       // Home score position 3
-    guest_score_1: u8,      // Guest score position 1
-    guest_score_2: u8,      // Guest score position 2
-    guest_score_3: u8,      // Guest score position 3
-}
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// Log to decode
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
 
-#[derive(Debug)]
-struct Message31{
-    id_1: u8,               // First byte of message ID
-    id_2: u8,               // Second byte of message ID
-    sports_id: u8,          // This needs to be 5 for basketball
-    byte_4: Option<u8>,     // Reserved / unused
-    home_fouls: u8,         // Home fouls
-    byte_6: Option<u8>,     // Reserved / unused
-    guest_fouls: u8,        // Guest fouls
-    number_player_on_line_1: u8, // Number of player on line position 1
-    number_player_on_line_2: u8, // Number of player on line position 2
-    number_of_faults_of_player: u8, // Number of faults of player 
-    team_of_player: u8,    // Team of player
-}
+    /// Format of the log
+    #[arg(long, value_name = "FORMAT", default_value = "session")]
+    format: LogFormat,
 
-#[derive(Debug)]
-struct Message36{
-    id_1: u8,               // First byte of message ID
-    id_2: u8,               // Second byte of message ID
-    seconds_1: u8,         // Seconds * 10
-    seconds_2: u8,         // Seconds * 1
-    seconds_3: u8,         // Seconds * 0.1
+    #[command(flatten)]
+    game: GameArgs,
 }
 
-#[derive(Debug)]
-struct Message50{
-    id_1: u8,               // First byte of message ID
-    id_2: u8,               // Second byte of message ID
-    status_word: u8,        // Status word
-    seconds_1: u8,         // Seconds * 10
-    seconds_2: u8,         // Seconds * 1
-}
+#[derive(Args, Debug)]
+struct SimulateArgs {
+    /// Server to send the game to, live; without it the game is written to
+    /// stdout as a log
+    #[arg(long, value_name = "HOST:PORT")]
+    to: Option<String>,
 
-struct StatusWord18 {
-    clock_type: bool,          // bit 0
-    game_clock_off: bool,      // bit 1
-    horn_on: bool,             // bit 2
-    possession_in_tenth: bool, // bit 4
-    new_match: bool,           // bit 6
-    b7: bool,                  // bit 7
+    /// How many times faster than real time to play
+    #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+    speed: f64,
+
+    /// Regulation periods
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=9))]
+    periods: u8,
+
+    /// Minutes of a regulation period
+    #[arg(long, value_name = "MINUTES", default_value_t = 10, value_parser = clap::value_parser!(u8).range(1..=99))]
+    period_minutes: u8,
+
+    /// Seed of the game; the same seed plays the same game
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+
+    /// Format of the log written to stdout
+    #[arg(long, value_name = "FORMAT", default_value = "session")]
+    format: LogFormat,
 }
 
-impl StatusWord18 {
-    fn from_byte(byte: u8) -> Self {
-        Self {
-            clock_type: (byte & (1 << 0)) != 0,
-            game_clock_off: (byte & (1 << 1)) != 0,
-            horn_on: (byte & (1 << 2)) != 0,
-            possession_in_tenth: (byte & (1 << 4)) != 0,
-            new_match: (byte & (1 << 6)) != 0,
-            b7: (byte & (1 << 7)) != 0,
-        }
-    }
+#[derive(Args, Debug)]
+struct ConvertArgs {
+    /// Log to convert
+    #[arg(value_name = "FILE")]
+    input: PathBuf,
+
+    /// Where to write the converted log [default: stdout]
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Format of the input
+    #[arg(long, value_name = "FORMAT", default_value = "session")]
+    from: LogFormat,
+
+    /// Format of the output
+    #[arg(long, value_name = "FORMAT")]
+    to: LogFormat,
 }
 
-struct StatusWord50 {
-    b0: Option<bool>, // bit 0
-    status_possession_timer: bool,       
-    status_possession_horn: bool,              // bit 2
-    status_of_shot_clock: bool,                 // bit 2
-    possession_timer_in_tenths: bool,        // bit 4
-    b5: Option<bool>,                        // bit 6
-    b6: Option<bool>,                        // bit 7
-    b7: bool,                                // bit 7
+/// A playback speed factor, above 0.
+fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("{:?} is not a speed above 0", value)),
+    }
 }
 
-impl StatusWord50 {
-    fn from_byte(byte: u8) -> Self {
-        Self {
-            b0: None,
-            status_possession_timer: (byte & (1 << 1)) != 0,
-            status_possession_horn: (byte & (1 << 2)) != 0,
-            status_of_shot_clock: (byte & (1 << 3)) != 0,
-            possession_timer_in_tenths: (byte & (1 << 4)) != 0,
-            b5: None,
-            b6: None,
-            b7: (byte & (1 << 7)) != 0,
-        }
+/// An offset into a log as seconds, M:SS or H:MM:SS, each with an optional
+/// fraction of a second.
+fn parse_offset(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("{:?} is not a time such as 90, 1:30 or 1:02:03.5", value);
+    let mut parts = value.rsplit(':');
+    let seconds: f64 = parts.next().and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+    let mut whole_minutes = 0.0;
+    for (part, unit) in parts.zip([1.0, 60.0]) {
+        let count: u32 = part.parse().map_err(|_| invalid())?;
+        whole_minutes += count as f64 * unit;
+    }
+    if value.matches(':').count() > 2 {
+        return Err(invalid());
     }
+    Duration::try_from_secs_f64(whole_minutes * 60.0 + seconds).map_err(|_| invalid())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GameState {
-    pub home_score: String,
-    pub away_score: String,
-    pub time: String,
-    pub period_name: String,
-    pub home_fouls: String,
-    pub away_fouls: String,
-    pub home_timeouts: String,
-    pub away_timeouts: String,
-    pub possession: Option<String>, // "Home", "Away", or None
-    pub game_state: String, // "pre-game", "running", "paused", etc.
-    pub shot_clock: Option<String>,
+/// An offset into a log as M:SS.mmm.
+fn format_offset(offset: Duration) -> String {
+    let millis = offset.as_millis();
+    format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        Self {
-            home_score: "-".to_string(),
-            away_score: "-".to_string(),
-            time: "--:--".to_string(),
-            period_name: "-".to_string(),
-            home_fouls: "-".to_string(),
-            away_fouls: "-".to_string(),
-            home_timeouts: "-".to_string(),
-            away_timeouts: "-".to_string(),
-            possession: None,
-            game_state: "pre-game".to_string(),
-            shot_clock: None,
+impl GameArgs {
+    /// The config file, if any, with these options applied.
+    fn load(&self) -> Result<Config, ConfigError> {
+        let mut config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+        if let Some(limit) = self.foul_limit {
+            config.foul_limit = limit;
         }
+        if let Some(format) = self.period_format {
+            config.period_format = format;
+        }
+        Ok(config)
     }
 }
 
-fn parse_valid_frame(frame: ProtocolFrame, game_state: &Arc<Mutex<GameState>>, broadcast_tx: &tokio::sync::broadcast::Sender<String>) {
-    // Ensure there's enough data to read the message type
-    if frame.message.len() < 2 {
-        warn!("Message too short to determine type");
-        return;
+impl WebArgs {
+    /// The config file, if any, with these options applied.
+    fn load(&self) -> Result<Config, ConfigError> {
+        let mut config = self.game.load()?;
+        if let Some(address) = self.web_address {
+            config.web_address = address;
+        }
+        if let Some(port) = self.web_port {
+            config.web_port = port;
+        }
+        if let Some(dir) = &self.static_dir {
+            config.static_dir = dir.clone();
+        }
+        if let Some(routing) = self.court_routing {
+            config.court_routing = routing;
+        }
+        Ok(config)
     }
+}
 
-    let mut state_changed = false;
-    let mut updated_state = {
-        let current = game_state.lock().unwrap();
-        current.clone()
-    };
-
-    // First two bytes of the message indicate the message type
-    match (frame.message[0], frame.message[1]) {
-        // Message Type 18
-        (0x31, 0x38) => {
-            info!("Received Message Type 18 (Game Time and Time-outs)");
-
-            // Ensure there's enough data for Message Type 18
-            if frame.message.len() < 14 {
-                warn!("Message Type 18 too short");
-                return;
-            }
-
-            // Construct the Message 18 struct
-            let message = Message18 {
-                id_1: frame.message[0],
-                id_2: frame.message[1],
-                status_word: frame.message[2],
-                sports_id: frame.message[3],
-                minutes_1: frame.message[4],
-                minutes_2: frame.message[5],
-                seconds_1: frame.message[6],
-                seconds_2: frame.message[7],
-                home_time_outs: frame.message[8],
-                guest_time_outs: frame.message[9],
-                byte_11: None,
-                byte_12: None,
-                period: frame.message[12],
-                byte_14: None,
-            };
-
-            let status_word = StatusWord18::from_byte(message.status_word);
-
-            info!(
-                "Status Word - Clock Type: {}, Game Clock Off: {}, Horn On: {}, Possession in Tenth: {}, New Match: {}, B7: {}",
-                status_word.clock_type,
-                status_word.game_clock_off,
-                status_word.horn_on,
-                status_word.possession_in_tenth,
-                status_word.new_match,
-                status_word.b7
-            );
-
-            if status_word.game_clock_off {
-                info!("Game Clock is OFF");
-                updated_state.game_state = "paused".to_string();
-            } else {
-                info!("Game Clock is ON");
-                updated_state.game_state = "running".to_string();
-            }
+/// The config file, if any, with the command-line options applied, checked
+/// before anything is started.
+fn load_config(cli: &ServerArgs) -> Result<Config, ConfigError> {
+    let mut config = cli.web.load()?;
 
-            if status_word.possession_in_tenth {
-                let minutes = format!("{}{}", message.minutes_1 as char, message.minutes_2 as char);
-                
-                if minutes == "00" {
-                    info!(
-                        "{}.{}",
-                        message.seconds_1 as char, message.seconds_2 as char
-                    );
-                    updated_state.time = format!("{}.{}", message.seconds_1 as char, message.seconds_2 as char);
-                } else {
-                    info!(
-                        "{}{}.{}",
-                        message.minutes_1 as char, message.minutes_2 as char, message.seconds_2 as char
-                    );
-                    updated_state.time = format!("{}{}.{}", message.minutes_1 as char, message.minutes_2 as char, message.seconds_2 as char);
-                }
-            } else {
-                info!(
-                    "{}{}:{}{}",
-                    message.minutes_1 as char,
-                    message.minutes_2 as char,
-                    message.seconds_1 as char,
-                    message.seconds_2 as char
-                );
-                updated_state.time = format!("{}{}:{}{}", message.minutes_1 as char, message.minutes_2 as char, message.seconds_1 as char, message.seconds_2 as char);
-            }
-
-            info!(
-                "Home Time-outs: {}, Guest Time-outs: {}, Period: {}",
-                message.home_time_outs as char,
-                message.guest_time_outs as char,
-                message.period as char
-            );
+    if let Some(address) = cli.tcp_address {
+        config.tcp_address = address;
+    }
+    if let Some(port) = cli.tcp_port {
+        config.tcp_port = port;
+    }
+    if let Some(dir) = &cli.log_dir {
+        config.log_dir = dir.clone();
+    }
+    if let Some(level) = &cli.log_level {
+        config.log_level = level.clone();
+    }
 
-            updated_state.home_timeouts = (message.home_time_outs as char).to_string();
-            updated_state.away_timeouts = (message.guest_time_outs as char).to_string();
-            let period_char = message.period as char;
-            updated_state.period_name = match period_char {
-                'O' => "Overtime".to_string(),
-                '1' | '2' | '3' | '4' => format!("{} Quarter", period_char),
-                _ => String::new(),
-            };
+    config.validate()?;
+    Ok(config)
+}
 
-            if status_word.possession_in_tenth {
-                updated_state.possession = Some("Home".to_string());
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        None => run_server(&cli.server),
+        Some(Command::Server(args)) => run_server(&args),
+        Some(command) => {
+            // Only warnings, so the output of the tools stays readable
+            env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
+            match command {
+                Command::Replay(args) => run_replay(&args),
+                Command::Inspect(args) => run_inspect(&args),
+                Command::Simulate(args) => run_simulate(&args),
+                Command::Convert(args) => run_convert(&args),
+                Command::Server(_) => unreachable!(),
             }
-
-            state_changed = true;
         }
-        // Message Type 30
-        (0x33, 0x30) => {
-            info!("Received Message Type 30 (Scores)");
-
-            // Ensure there's enough data for Message Type 30
-            if frame.message.len() < 9 {
-                warn!("Message Type 30 too short");
-                return;
-            }
-
-            let message = Message30 {
-                id_1: frame.message[0],
-                id_2: frame.message[1],
-                sports_id: frame.message[2],
-                home_score_1: frame.message[3],
-                home_score_2: frame.message[4],
-                home_score_3: frame.message[5],
-                guest_score_1: frame.message[6],
-                guest_score_2: frame.message[7],
-                guest_score_3: frame.message[8],
-            };
-
-            info!(
-                "Home Score: {}{}{}, Guest Score: {}{}{}",
-                message.home_score_1 as char,
-                message.home_score_2 as char,
-                message.home_score_3 as char,
-                message.guest_score_1 as char,
-                message.guest_score_2 as char,
-                message.guest_score_3 as char
-            );
+    };
 
-            updated_state.home_score = format!("{}{}{}", message.home_score_1 as char, message.home_score_2 as char, message.home_score_3 as char);
-            updated_state.away_score = format!("{}{}{}", message.guest_score_1 as char, message.guest_score_2 as char, message.guest_score_3 as char);
-            state_changed = true;
+    if let Err(e) = result {
+        // Output piped into e.g. `head` that stopped reading
+        if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) {
+            return;
         }
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
 
-        // Message Type 31
-        (0x33, 0x31) => {
-            info!("Received Message Type 31 (Fouls and Player Info)");
-
-            // Ensure there's enough data for Message Type 31
-            if frame.message.len() < 11 {
-                warn!("Message Type 31 too short");
-                return;
-            }
-
-            let message = Message31 {
-                id_1: frame.message[0],
-                id_2: frame.message[1],
-                sports_id: frame.message[2],
-                byte_4: None,
-                home_fouls: frame.message[4],
-                byte_6: None,
-                guest_fouls: frame.message[6],
-                number_player_on_line_1: frame.message[7],
-                number_player_on_line_2: frame.message[8],
-                number_of_faults_of_player: frame.message[9],
-                team_of_player: frame.message[10],
-            };
-
-            info!(
-                "Home Fouls: {}, Guest Fouls: {}, Player on Line 1: {}, Player on Line 2: {}, Faults of Player: {}, Team of Player: {}",
-                message.home_fouls as char,
-                message.guest_fouls as char,
-                message.number_player_on_line_1 as char,
-                message.number_player_on_line_2 as char,
-                message.number_of_faults_of_player as char,
-                message.team_of_player as char
-            );
-
-            updated_state.home_fouls = (message.home_fouls as char).to_string();
-            updated_state.away_fouls = (message.guest_fouls as char).to_string();
-            state_changed = true;
-        }
+/// The state every game starts from, with the competition settings of the
+/// config.
+fn template_state(config: &Config) -> GameState {
+    info!("Players foul out at {} personal fouls", config.foul_limit);
+    GameState {
+        player_fouls: FoulLedger::new(config.foul_limit),
+        period_format: config.period_format,
+        ..GameState::default()
+    }
+}
 
-        // Message Type 50
-        (0x35, 0x30) => {
-            info!("Received Message Type 50 (Shot Clock)");
+/// The courts of a server, routed as the config says.
+fn new_courts(config: &Config) -> Arc<Courts> {
+    info!("Courts routing: {:?}", config.court_routing);
+    Arc::new(Courts::new(config.court_routing, template_state(config)))
+}
 
-            // Ensure there's enough data for Message Type 50
-            if frame.message.len() < 5 {
-                warn!("Message Type 50 too short");
-                return;
+/// Serve the overlay and the API of `courts` on a thread of its own. Failing
+/// to listen exits the process.
+fn spawn_web_server(courts: Arc<Courts>, config: &Config) {
+    let web_address = config.web_socket();
+    let static_dir = config.static_dir.clone();
+    thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            if let Err(e) = start_web_server(courts, web_address, static_dir).await {
+                error!("Failed to listen on {}: {}", web_address, e);
+                process::exit(1);
             }
+        });
+    });
+}
 
-            let message = Message50 {
-                id_1: frame.message[0],
-                id_2: frame.message[1],
-                status_word: frame.message[2],
-                seconds_1: frame.message[3],
-                seconds_2: frame.message[4],
-            };
+/// Run the ingest and web servers until the process is stopped. Only
+/// returns on a config error; failing to listen exits the process.
+fn run_server(args: &ServerArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // The logger is configured by the config, so its errors go to stderr
+    let config = load_config(args)?;
+
+    // Initialize logger: --log-level, else RUST_LOG, else the config level
+    let mut logger = env_logger::Builder::from_env(Env::default().default_filter_or(config.log_level.as_str()));
+    if args.log_level.is_some() {
+        if let Ok(level) = config.level_filter() {
+            logger.filter_level(level);
+        }
+    }
+    logger.init();
 
-            let status_word = StatusWord50::from_byte(message.status_word);
-            
-            if status_word.possession_timer_in_tenths {
-                info!(
-                    "Shot Clock Time: {}.{}",
-                    message.seconds_1 as char, message.seconds_2 as char
-                );
-                updated_state.shot_clock = Some(format!("{}.{}", message.seconds_1 as char, message.seconds_2 as char));
-            } else {
-                info!(
-                    "Shot Clock Time: {}{}",
-                    message.seconds_1 as char, message.seconds_2 as char
-                );
-                updated_state.shot_clock = Some(format!("{}{}", message.seconds_1 as char, message.seconds_2 as char));
-            }
-            state_changed = true;
+    // In dev mode, do NOT log TCP session bytes to files
+    let log_dir = if args.dev {
+        info!("Starting in dev mode: TCP session bytes will NOT be logged to files");
+        None
+    } else {
+        if let Err(e) = std::fs::create_dir_all(&config.log_dir) {
+            error!("Cannot create log directory {}: {}", config.log_dir.display(), e);
+            process::exit(1);
         }
+        info!("Logging sessions and finished games to {}", config.log_dir.display());
+        Some(config.log_dir.clone())
+    };
 
-        // Message 36
-        (0x33, 0x36) => {
-            // info!("Received Message Type 36 (Possession Time Last Minute)");
+    let courts = new_courts(&config);
+    spawn_web_server(Arc::clone(&courts), &config);
 
-            // Ensure there's enough data for Message Type 36
-            if frame.message.len() < 5 {
-                warn!("Message Type 36 too short");
-                return;
-            }
+    let tcp_address = config.tcp_socket();
 
-            // Construct the Message 36 struct
-            let message = Message36 {
-                id_1: frame.message[0],
-                id_2: frame.message[1],
-                seconds_1: frame.message[2],
-                seconds_2: frame.message[3],
-                seconds_3: frame.message[4],
-            };
+    if let Err(e) = tcp_server::run(tcp_address, log_dir, courts) {
+        error!("Failed to listen on {}: {}", tcp_address, e);
+        process::exit(1);
+    }
+    Ok(())
+}
 
-            updated_state.time = format!("{}{}.{}", message.seconds_1 as char, message.seconds_2 as char, message.seconds_3 as char);
-            state_changed = true;
+fn run_replay(args: &ReplayArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if let (Some(start), Some(end)) = (args.start, args.end) {
+        if end <= start {
+            return Err("--end must be after --start".into());
         }
-
-        _ => {
+    }
+    let logs = args
+        .files
+        .iter()
+        .map(|file| read_log(file, args.format))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (file, log) in args.files.iter().zip(&logs) {
+        if !log.is_empty() && log.iter().all(|chunk| chunk.at.is_none()) {
             warn!(
-                "Unknown message type: 0x{:02X} 0x{:02X}",
-                frame.message[0], frame.message[1]
+                "{} has no read times, playing its reads {} ms apart instead of with their original timing",
+                file.display(),
+                args.interval
             );
         }
     }
 
-    if state_changed {
-        *game_state.lock().unwrap() = updated_state.clone();
-        if let Ok(json) = serde_json::to_string(&updated_state) {
-            let _ = broadcast_tx.send(json);
-        }
-    }
-}
-
-fn main() {
-    // Parse command-line args and determine if we're in dev mode.
-    // When started with an argument equal to "dev", do NOT log TCP session bytes to files.
-    let args: Vec<String> = std::env::args().collect();
-    let dev_mode = args.iter().any(|a| a == "dev");
-
-    // Initialize logger (reads RUST_LOG if set, defaults to `info` level)
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-
-    if dev_mode {
-        info!("Starting in dev mode: TCP session bytes will NOT be logged to files");
+    let replay = Replay {
+        pace: if args.step { Pace::Step } else { Pace::Speed(args.speed) },
+        interval: Duration::from_millis(args.interval),
+        start: args.start.unwrap_or_default(),
+        end: args.end,
+        looping: args.looping,
+    };
+    let timeline = replay.timeline(&logs);
+    if let Some(&(last, _)) = timeline.last() {
+        eprintln!("Replaying {} reads over {}", timeline.len(), format_offset(last));
     }
 
-    // Shared game state
-    let game_state = Arc::new(Mutex::new(GameState::default()));
-    
-    // Broadcast channel for SSE
-    let (broadcast_tx, _) = tokio::sync::broadcast::channel::<String>(100);
-
-    // Clone for the web server
-    let game_state_clone = Arc::clone(&game_state);
-    let broadcast_tx_clone = broadcast_tx.clone();
-
-    // Start web server in a separate thread
-    thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            start_web_server(game_state_clone, broadcast_tx_clone).await;
-        });
-    });
+    let (mut target, destination) = if args.in_process {
+        let config = args.web.load()?;
+        config.validate()?;
+        let courts = new_courts(&config);
+        spawn_web_server(Arc::clone(&courts), &config);
+        eprintln!("Overlay on http://{}/", config.web_socket());
+        (Target::courts(courts), "this process".to_string())
+    } else {
+        let target = Target::connect(&args.to).map_err(|e| format!("Cannot connect to {}: {}", args.to, e))?;
+        (target, args.to.clone())
+    };
 
-    let tcp_address = "0.0.0.0:4001";
-
-    let listener = TcpListener::bind(&tcp_address).unwrap();
-    info!("Basketball Protocol Server listening on {}", tcp_address);
-    info!("Waiting for connections...");
-
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
-                // capture dev_mode (bool is Copy so this is fine)
-                let game_state_clone = Arc::clone(&game_state);
-                let broadcast_tx_clone = broadcast_tx.clone();
-                thread::spawn(move || {
-                    if let Err(e) = handle_client(stream, !dev_mode, game_state_clone, broadcast_tx_clone) {
-                        error!("Error handling client: {}", e);
-                    }
-                });
-            }
-            Err(e) => {
-                error!("Error accepting connection: {}", e);
-            }
+    let stdin = io::stdin();
+    let step = |at: Duration, chunk: &Chunk| {
+        eprint!("{}  \"{}\"  (Enter: send, q: quit) ", format_offset(at), chunk.bytes.escape_ascii());
+        let mut answer = String::new();
+        Ok(stdin.read_line(&mut answer)? > 0 && answer.trim() != "q")
+    };
+    let sent = replay
+        .play(&timeline, &mut target, step)
+        .map_err(|e| format!("Replay to {} failed: {}", destination, e))?;
+    eprintln!("Sent {} reads to {}", sent, destination);
+
+    if args.in_process {
+        eprintln!("Replay finished, still serving the overlay until interrupted");
+        loop {
+            thread::park();
         }
     }
+    Ok(())
 }
 
-// Handle a single client connection
-fn handle_client(mut stream: TcpStream, log_to_file: bool, game_state: Arc<Mutex<GameState>>, broadcast_tx: tokio::sync::broadcast::Sender<String>) -> std::io::Result<()> {
-    let peer_addr = stream.peer_addr()?;
-    info!("New connection from: {}", peer_addr);
-
-    // Set read timeout to prevent hanging
-    stream.set_read_timeout(Some(Duration::from_secs(300)))?;
-
-    // Create `data_log/` directory and open a new per-session file named with a timestamp
-    // only if file logging is enabled. Do not write a header — raw bytes only.
-    // Failures to create/open the file are logged and do not terminate the client connection.
-    let mut log_file: Option<std::fs::File> = if log_to_file {
-        // ensure directory exists
-        if let Err(e) = std::fs::create_dir_all("data_log") {
-            error!("Failed to create data_log directory: {}", e);
-        }
+fn run_inspect(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = args.game.load()?;
+    config.validate_game()?;
+    let chunks = read_log(&args.file, args.format)?;
+    let mut state = template_state(&config);
+    inspect(&chunks, &mut state, &mut io::stdout().lock())?;
+    Ok(())
+}
 
-        // timestamp-based filename (seconds + millis to reduce collisions)
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default();
-        let file_name = format!(
-            "data_log/session-{}.{}.log",
-            now.as_secs(),
-            now.subsec_millis()
-        );
-
-        match OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_name)
-        {
-            Ok(f) => {
-                info!("Logging TCP session to {}", file_name);
-                Some(f)
-            }
-            Err(e) => {
-                error!("Failed to open session log file {}: {}", file_name, e);
-                None
-            }
-        }
-    } else {
-        info!("Session file logging is disabled for this run");
-        None
+fn run_simulate(args: &SimulateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let simulation = Simulation {
+        periods: args.periods,
+        period_minutes: args.period_minutes,
+        seed: args.seed,
     };
-
-    let mut buffer = [0u8; 1024];
-
-    loop {
-        match stream.read(&mut buffer) {
-            Ok(0) => {
-                // Connection closed
-                info!("Connection closed by: {}", peer_addr);
-                break;
-            }
-            Ok(n) => {
-                // Write each TCP read as a single newline-delimited line containing
-                // a hex-style byte array (matching the debug output), e.g.:
-                // [01, 7F, 02, ...]
-                if let Some(ref mut f) = log_file {
-                    let line = format!("{:02X?}\n", &buffer[..n]);
-                    if let Err(e) = f.write_all(line.as_bytes()) {
-                        warn!("Failed to write raw bytes to log file: {}", e);
-                    }
-                    // best-effort flush to ensure data is on-disk quickly
-                    if let Err(e) = f.flush() {
-                        warn!("Failed to flush log file: {}", e);
-                    }
-                }
-
-                // Attempt to parse the received bytes as a ProtocolFrame
-                match parse_raw_data(&buffer[..n]) {
-                    Ok(frame) => {
-                        // info!(
-                        //     "Parsed ProtocolFrame: SOH={:02X}, ADDR={:02X}, STX={:02X}, CTRL={:02X}, MESSAGE={:02X?}, ETX={:02X}, LRC={:02X}",
-                        //     frame.soh,
-                        //     frame.address,
-                        //     frame.stx,
-                        //     frame.ctrl,
-                        //     frame.message,
-                        //     frame.etx,
-                        //     frame.lrc
-                        // );
-
-                        parse_valid_frame(frame, &game_state, &broadcast_tx);
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse ProtocolFrame from {}: {}", peer_addr, e);
-                    }
-                }
-            }
-            Err(e) => {
-                error!("Error reading from {}: {}", peer_addr, e);
-                break;
-            }
+    let frames = simulation.frames();
+
+    let chunks: Vec<Chunk> = frames
+        .into_iter()
+        .map(|(at, frame)| Chunk {
+            at: Some(at),
+            bytes: frame.to_bytes(),
+        })
+        .collect();
+
+    match &args.to {
+        Some(to) => {
+            let mut target = Target::connect(to).map_err(|e| format!("Cannot connect to {}: {}", to, e))?;
+            let replay = Replay {
+                pace: Pace::Speed(args.speed),
+                ..Replay::default()
+            };
+            let sent = replay
+                .play(&replay.timeline(&[chunks]), &mut target, |_, _| Ok(true))
+                .map_err(|e| format!("Simulation to {} failed: {}", to, e))?;
+            eprintln!("Sent {} frames to {}", sent, to);
         }
+        None => write_log(&mut io::stdout().lock(), args.format, &chunks)?,
     }
+    Ok(())
+}
 
+fn run_convert(args: &ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let chunks = read_log(&args.input, args.from)?;
+    match &args.output {
+        Some(path) => {
+            let mut file = File::create(path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+            write_log(&mut file, args.to, &chunks)?;
+        }
+        None => write_log(&mut io::stdout().lock(), args.to, &chunks)?,
+    }
     Ok(())
 }
 
-async fn start_web_server(game_state: Arc<Mutex<GameState>>, broadcast_tx: tokio::sync::broadcast::Sender<String>) {
-    // GET / -> serve overlay.html
-    let index = warp::path::end()
-        .and(warp::fs::file("./static/overlay.html"));
-
-    // GET /overlay.css -> serve overlay.css
-    let css = warp::path("overlay.css")
-        .and(warp::fs::file("./static/overlay.css"));
-
-    // GET /overlay.js -> serve overlay.js
-    let js = warp::path("overlay.js")
-        .and(warp::fs::file("./static/overlay.js"));
-
-    // GET /media/* -> serve assets from static/media
-    let media = warp::path("media")
-        .and(warp::fs::dir("./static/media"));
-
-    // GET /api/game -> return current game state
-    let game_state_filter = warp::any().map(move || Arc::clone(&game_state));
-    let game_api = warp::path!("api" / "game")
-        .and(game_state_filter)
-        .map(|state: Arc<Mutex<GameState>>| {
-            let state = state.lock().unwrap();
-            warp::reply::json(&*state)
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_offsets() {
+        assert_eq!(parse_offset("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_offset("0.25"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_offset("1:30"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_offset("12:30.5"), Ok(Duration::from_millis(750_500)));
+        assert_eq!(parse_offset("1:02:03.5"), Ok(Duration::from_millis(3_723_500)));
+    }
 
-    // GET /api/stream -> SSE endpoint
-    let broadcast_filter = warp::any().map(move || broadcast_tx.subscribe());
-    let stream_api = warp::path!("api" / "stream")
-        .and(broadcast_filter)
-        .map(|mut rx: tokio::sync::broadcast::Receiver<String>| {
-            
-            let stream = async_stream::stream! {
-                // Send current state immediately
-                {
-                    let current_state = serde_json::to_string(&GameState::default()).unwrap_or_default();
-                    yield Ok::<_, std::convert::Infallible>(warp::sse::Event::default().data(current_state));
-                }
-                
-                while let Ok(data) = rx.recv().await {
-                    yield Ok::<_, std::convert::Infallible>(warp::sse::Event::default().data(data));
-                }
-            };
-            
-            warp::sse::reply(warp::sse::keep_alive().stream(stream))
-        });
+    #[test]
+    fn malformed_offsets() {
+        for offset in ["", "abc", "-5", "1:x", "1::30", ":30", "1:2:3:4", "1.5:30", "inf", "NaN"] {
+            assert!(parse_offset(offset).is_err(), "{:?} was accepted", offset);
+        }
+    }
 
-    let routes = index
-        .or(css)
-        .or(js)
-        .or(media)
-        .or(game_api)
-        .or(stream_api);
-
-    info!("Web server starting on http://localhost:3030");
-    warp::serve(routes)
-        .run(([0, 0, 0, 0], 3030))
-        .await;
+    #[test]
+    fn formatted_offsets() {
+        assert_eq!(format_offset(Duration::ZERO), "0:00.000");
+        assert_eq!(format_offset(Duration::from_millis(750_500)), "12:30.500");
+        assert_eq!(format_offset(Duration::from_millis(3_723_500)), "62:03.500");
+        assert_eq!(parse_offset(&format_offset(Duration::from_millis(3_723_500))), Ok(Duration::from_millis(3_723_500)));
+    }
 }
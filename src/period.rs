@@ -0,0 +1,244 @@
+//! Period model: the period being played, counted through successive
+//! overtimes, and the break that follows it.
+//!
+//! The scorepad only sends a period byte: a digit for regulation periods and
+//! 'O' (basketball) or 'E' (handball, futsal) for every overtime. Overtimes
+//! are told apart by the rest timer: an overtime that was played and then
+//! followed by a break is over, so the next game clock in overtime starts
+//! the next one.
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::game_state::ClockType;
+use crate::sport::Sport;
+
+/// How the regulation time of a basketball game is divided. The scorepad
+/// sends the same period digits for both, so this is configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodFormat {
+    #[default]
+    Quarters, // FIBA, NBA
+    Halves,   // e.g. NCAA men
+}
+
+impl FromStr for PeriodFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "quarters" => Ok(PeriodFormat::Quarters),
+            "halves" => Ok(PeriodFormat::Halves),
+            _ => Err(format!("unknown period format {:?}, expected \"quarters\" or \"halves\"", value)),
+        }
+    }
+}
+
+/// Kind of a period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodKind {
+    Quarter,
+    Half,
+    Period, // basketball 3x3 plays one period
+    Set,    // volleyball
+    Overtime,
+}
+
+/// Break between two periods, while the rest timer runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    Break,
+    Halftime,
+}
+
+/// The current period, or the break after it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Period {
+    pub number: u8,                 // 1-based, counting on through overtimes: OT1 after 4 quarters is 5
+    pub kind: PeriodKind,
+    pub overtime: Option<u8>,       // overtime number, None in regulation
+    pub interval: Option<Interval>, // the rest timer runs after this period
+    #[serde(skip)]
+    played: bool,                   // the game clock ran in this period
+}
+
+impl Period {
+    /// Period shown by a period byte, following `previous`. `None` for a
+    /// blank byte, as before the match.
+    ///
+    /// `extra_time` is the overtime number when the scorepad sends one
+    /// (futsal), `clock_type` the clock shown with the byte.
+    pub fn from_byte(
+        previous: Option<&Period>,
+        sport: Sport,
+        format: PeriodFormat,
+        byte: u8,
+        extra_time: Option<u8>,
+        clock_type: ClockType,
+    ) -> Option<Period> {
+        let regulation = regulation_periods(sport, format);
+        let resting = clock_type == ClockType::Rest;
+
+        let (number, kind, overtime, played) = match byte {
+            b'1'..=b'9' => {
+                let number = byte - b'0';
+                let played = !resting || previous.is_some_and(|p| p.number == number && p.played);
+                (number, regulation_kind(sport, format), None, played)
+            }
+            b'O' | b'E' => {
+                let overtime = match (extra_time, previous.and_then(|p| p.overtime.map(|ot| (ot, p)))) {
+                    (Some(overtime), _) => overtime,
+                    // Game clock back after a break that followed this overtime
+                    (None, Some((overtime, p))) if p.played && p.interval.is_some() && !resting => overtime + 1,
+                    (None, Some((overtime, _))) => overtime,
+                    (None, None) => 1,
+                };
+                let played = !resting
+                    || previous.is_some_and(|p| p.overtime == Some(overtime) && p.played);
+                (regulation + overtime, PeriodKind::Overtime, Some(overtime), played)
+            }
+            _ => return None,
+        };
+
+        let interval = resting.then(|| {
+            if overtime.is_none() && number * 2 == regulation {
+                Interval::Halftime
+            } else {
+                Interval::Break
+            }
+        });
+
+        Some(Period {
+            number,
+            kind,
+            overtime,
+            interval,
+            played,
+        })
+    }
+
+    /// Display name, e.g. "2 Quarter", "OT1", "Set 3" or "Halftime".
+    pub fn name(&self, sport: Sport) -> String {
+        match (self.interval, self.kind, self.overtime) {
+            (Some(Interval::Halftime), _, _) => "Halftime".to_string(),
+            (Some(Interval::Break), _, _) => "Break".to_string(),
+            (None, PeriodKind::Overtime, Some(overtime)) => match sport {
+                Sport::Handball | Sport::Futsal => format!("Extra Time {}", overtime),
+                _ => format!("OT{}", overtime),
+            },
+            (None, PeriodKind::Quarter, _) => format!("{} Quarter", self.number),
+            (None, PeriodKind::Half, _) => format!("{} Half", self.number),
+            (None, PeriodKind::Period, _) => format!("Period {}", self.number),
+            (None, PeriodKind::Set, _) => format!("Set {}", self.number),
+            (None, PeriodKind::Overtime, None) => "Overtime".to_string(),
+        }
+    }
+}
+
+/// Kind of the regulation periods of a sport.
+fn regulation_kind(sport: Sport, format: PeriodFormat) -> PeriodKind {
+    match (sport, format) {
+        (Sport::Basketball, PeriodFormat::Quarters) => PeriodKind::Quarter,
+        (Sport::Basketball, PeriodFormat::Halves) | (Sport::Handball, _) | (Sport::Futsal, _) => PeriodKind::Half,
+        (Sport::Basketball3x3, _) => PeriodKind::Period,
+        (Sport::Volleyball, _) => PeriodKind::Set,
+    }
+}
+
+/// Number of regulation periods of a sport; overtimes are numbered after
+/// them.
+pub fn regulation_periods(sport: Sport, format: PeriodFormat) -> u8 {
+    match regulation_kind(sport, format) {
+        PeriodKind::Quarter => 4,
+        PeriodKind::Half => 2,
+        PeriodKind::Period => 1,
+        PeriodKind::Set => 5,
+        PeriodKind::Overtime => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ClockType::{Game, Rest};
+
+    /// Feed period bytes with their clock, as a scorepad sends them, and
+    /// return the name of the period after each.
+    fn play(sport: Sport, format: PeriodFormat, sent: &[(u8, ClockType)]) -> Vec<String> {
+        let mut period: Option<Period> = None;
+        sent.iter()
+            .map(|&(byte, clock_type)| {
+                period = Period::from_byte(period.as_ref(), sport, format, byte, None, clock_type);
+                period.as_ref().map(|p| p.name(sport)).unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quarters() {
+        let names = play(
+            Sport::Basketball,
+            PeriodFormat::Quarters,
+            &[(b'1', Game), (b'1', Rest), (b'2', Game), (b'2', Rest), (b'3', Game), (b'4', Game)],
+        );
+        assert_eq!(names, ["1 Quarter", "Break", "2 Quarter", "Halftime", "3 Quarter", "4 Quarter"]);
+        assert_eq!(regulation_periods(Sport::Basketball, PeriodFormat::Quarters), 4);
+    }
+
+    #[test]
+    fn halves() {
+        let names = play(
+            Sport::Basketball,
+            PeriodFormat::Halves,
+            &[(b'1', Game), (b'1', Rest), (b'2', Game), (b'2', Rest), (b'O', Game)],
+        );
+        assert_eq!(names, ["1 Half", "Halftime", "2 Half", "Break", "OT1"]);
+        assert_eq!(regulation_periods(Sport::Basketball, PeriodFormat::Halves), 2);
+    }
+
+    #[test]
+    fn first_overtime_follows_regulation() {
+        let quarter = Period::from_byte(None, Sport::Basketball, PeriodFormat::Quarters, b'4', None, Game);
+        let overtime = Period::from_byte(quarter.as_ref(), Sport::Basketball, PeriodFormat::Quarters, b'O', None, Game).unwrap();
+        assert_eq!(overtime.number, 5);
+        assert_eq!(overtime.kind, PeriodKind::Overtime);
+        assert_eq!(overtime.overtime, Some(1));
+        assert_eq!(overtime.name(Sport::Basketball), "OT1");
+
+        let halves = Period::from_byte(None, Sport::Basketball, PeriodFormat::Halves, b'O', None, Game).unwrap();
+        assert_eq!(halves.number, 3);
+    }
+
+    #[test]
+    fn second_overtime_after_a_rest() {
+        let names = play(
+            Sport::Basketball,
+            PeriodFormat::Quarters,
+            &[
+                (b'4', Game),
+                (b'O', Rest), // break before OT1
+                (b'O', Game),
+                (b'O', Game),
+                (b'O', Rest), // break after OT1
+                (b'O', Game),
+                (b'O', Game),
+            ],
+        );
+        assert_eq!(names, ["4 Quarter", "Break", "OT1", "OT1", "Break", "OT2", "OT2"]);
+    }
+
+    #[test]
+    fn extra_time_number_sent_by_the_scorepad() {
+        let period = Period::from_byte(None, Sport::Futsal, PeriodFormat::default(), b'E', Some(2), Game).unwrap();
+        assert_eq!(period.number, 4);
+        assert_eq!(period.name(Sport::Futsal), "Extra Time 2");
+    }
+
+    #[test]
+    fn blank_period_before_the_match() {
+        assert_eq!(Period::from_byte(None, Sport::Basketball, PeriodFormat::Quarters, b' ', None, Rest), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Replay of session logs, or of made-up games, with their original timing:
+//! over TCP to a scoreboard server as a scorepad would send them, or
+//! straight into the courts of this process.
+//!
+//! Playback runs at the logged pace, faster or slower by a speed factor, or
+//! one read at a time. It can start and end at offsets into the log: the
+//! reads before the start are sent at once, so the scoreboard shows the
+//! state of the game at that time.
+//!
+//! Only logs with read times keep their timing; the reads of a log without
+//! them are played `interval` apart.
+
+use log::info;
+use std::io::{self, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::court::Courts;
+use crate::frame::FrameDecoder;
+use crate::session_log::Chunk;
+use crate::tcp_server::process_read;
+
+/// Time between the reads of a log that has no times, such as the session
+/// logs written before read times were recorded: their original timing is
+/// lost.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How fast reads are sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    /// At their logged time, divided by this factor: 1 is real time, 10 ten
+    /// times faster.
+    Speed(f64),
+    /// One read each time the step callback says so.
+    Step,
+}
+
+/// How logs are played back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub pace: Pace,
+    pub interval: Duration,    // after a read without a logged time
+    pub start: Duration,       // offset into the logs where playback starts
+    pub end: Option<Duration>, // offset where it stops, the end of the logs when None
+    pub looping: bool,         // start over once the end is reached
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            pace: Pace::Speed(1.0),
+            interval: DEFAULT_INTERVAL,
+            start: Duration::ZERO,
+            end: None,
+            looping: false,
+        }
+    }
+}
+
+/// Where replayed reads go.
+pub enum Target {
+    /// A scoreboard server, over one TCP connection.
+    Tcp(TcpStream),
+    /// The courts of this process, as if read from a scorepad on this host.
+    Courts { courts: Arc<Courts>, decoder: FrameDecoder },
+}
+
+impl Target {
+    /// Connect to the scorepad port of a server, e.g. "127.0.0.1:4001".
+    pub fn connect(address: &str) -> io::Result<Target> {
+        let stream = TcpStream::connect(address)?;
+        info!("Replaying to {}", address);
+        Ok(Target::Tcp(stream))
+    }
+
+    pub fn courts(courts: Arc<Courts>) -> Target {
+        Target::Courts {
+            courts,
+            decoder: FrameDecoder::new(),
+        }
+    }
+
+    /// Send the bytes of one read.
+    pub fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Target::Tcp(stream) => {
+                stream.write_all(bytes)?;
+                stream.flush()
+            }
+            Target::Courts { courts, decoder } => {
+                // Finished games are not archived, the log already holds them
+                process_read(decoder, bytes, courts, IpAddr::V4(Ipv4Addr::LOCALHOST));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Replay {
+    /// The reads of `logs` to play, with their offset into the logs. Each
+    /// log follows the previous one, `interval` after its last read. Reads
+    /// after `end` are left out.
+    pub fn timeline<'a>(&self, logs: &'a [Vec<Chunk>]) -> Vec<(Duration, &'a Chunk)> {
+        let mut timeline = Vec::new();
+        let mut log_start = Duration::ZERO;
+        for log in logs {
+            let mut previous: Option<Duration> = None;
+            for chunk in log {
+                let at = match (chunk.at, previous) {
+                    (Some(at), _) => log_start + at,
+                    (None, Some(previous)) => previous + self.interval,
+                    (None, None) => log_start,
+                };
+                previous = Some(at);
+                if self.end.is_some_and(|end| at > end) {
+                    continue;
+                }
+                timeline.push((at, chunk));
+            }
+            if let Some(previous) = previous {
+                log_start = previous + self.interval;
+            }
+        }
+        timeline
+    }
+
+    /// Play a timeline to `target`, sending the reads before `start` at
+    /// once. With `Pace::Step`, `step` is called before each later read,
+    /// with its offset, and playback stops when it returns false.
+    ///
+    /// Returns the number of reads sent.
+    pub fn play<F>(&self, timeline: &[(Duration, &Chunk)], target: &mut Target, mut step: F) -> io::Result<usize>
+    where
+        F: FnMut(Duration, &Chunk) -> io::Result<bool>,
+    {
+        let mut sent = 0;
+        if timeline.is_empty() {
+            return Ok(sent);
+        }
+
+        loop {
+            let started = Instant::now();
+            for &(at, chunk) in timeline {
+                match self.pace {
+                    Pace::Speed(speed) => {
+                        let offset = at.saturating_sub(self.start);
+                        if let Some(wait) = offset.div_f64(speed).checked_sub(started.elapsed()) {
+                            thread::sleep(wait);
+                        }
+                    }
+                    Pace::Step if at >= self.start => {
+                        if !step(at, chunk)? {
+                            return Ok(sent);
+                        }
+                    }
+                    Pace::Step => {}
+                }
+                target.send(&chunk.bytes)?;
+                sent += 1;
+            }
+
+            if !self.looping {
+                return Ok(sent);
+            }
+            info!("Replay looping after {} reads", sent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(times: &[Option<u64>]) -> Vec<Chunk> {
+        times
+            .iter()
+            .map(|at| Chunk {
+                at: at.map(Duration::from_millis),
+                bytes: vec![0x01],
+            })
+            .collect()
+    }
+
+    fn offsets(timeline: &[(Duration, &Chunk)]) -> Vec<u64> {
+        timeline.iter().map(|(at, _)| at.as_millis() as u64).collect()
+    }
+
+    #[test]
+    fn untimed_log_is_evenly_spaced() {
+        let logs = [log(&[None; 5])];
+        let replay = Replay {
+            interval: Duration::from_millis(250),
+            ..Replay::default()
+        };
+        assert_eq!(offsets(&replay.timeline(&logs)), [0, 250, 500, 750, 1000]);
+        assert_eq!(offsets(&Replay::default().timeline(&logs)), [0, 100, 200, 300, 400]);
+    }
+
+    #[test]
+    fn timed_log_keeps_its_times() {
+        let logs = [log(&[Some(0), Some(40), Some(1_500), Some(1_510)])];
+        assert_eq!(offsets(&Replay::default().timeline(&logs)), [0, 40, 1_500, 1_510]);
+    }
+
+    #[test]
+    fn logs_follow_each_other() {
+        let logs = [log(&[Some(0), Some(1_000)]), log(&[None, None]), log(&[Some(0), Some(500)])];
+        assert_eq!(
+            offsets(&Replay::default().timeline(&logs)),
+            [0, 1_000, 1_100, 1_200, 1_300, 1_800]
+        );
+    }
+
+    #[test]
+    fn reads_after_the_end_are_left_out() {
+        let logs = [log(&[None; 10])];
+        let replay = Replay {
+            end: Some(Duration::from_millis(300)),
+            ..Replay::default()
+        };
+        assert_eq!(offsets(&replay.timeline(&logs)), [0, 100, 200, 300]);
+    }
+}
@@ -0,0 +1,240 @@
+//! Session logs: the bytes a scorepad connection delivered, one TCP read per
+//! line, as the TCP server writes them to `session-<timestamp>.log`.
+//!
+//! Each line starts with the time of the read in seconds from the start of
+//! the session, e.g. `12.345 [01, 7F, 02, 47, ...]`, so that a replay can
+//! keep the original timing. Logs written before times were recorded hold
+//! the byte list alone.
+//!
+//! The same reads can be held as plain hex lines or as the raw byte stream,
+//! to feed other tools. `read_log` and `write_log` go between the formats.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::LogError;
+
+/// Raw logs don't keep read boundaries, so they are read back in chunks of
+/// this size, like the TCP server reads a connection.
+pub const RAW_CHUNK_LEN: usize = 1024;
+
+/// How the reads of a log are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// One read per line as a hex byte list: `12.345 [01, 7F, 02, 47, ...]`.
+    #[default]
+    Session,
+    /// One read per line as plain hex: `12.345 017F0247...`.
+    Hex,
+    /// The bytes as received, without read boundaries.
+    Raw,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "session" => Ok(LogFormat::Session),
+            "hex" => Ok(LogFormat::Hex),
+            "raw" => Ok(LogFormat::Raw),
+            _ => Err(format!("unknown log format {:?}, expected \"session\", \"hex\" or \"raw\"", value)),
+        }
+    }
+}
+
+/// One TCP read of a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub at: Option<Duration>, // time from the start of the session, None in logs without times
+    pub bytes: Vec<u8>,
+}
+
+/// The session log line of one read, without its newline.
+pub fn format_line(chunk: &Chunk) -> String {
+    match chunk.at {
+        Some(at) => format!("{} {:02X?}", format_time(at), chunk.bytes),
+        None => format!("{:02X?}", chunk.bytes),
+    }
+}
+
+/// A time of a log line: seconds with milliseconds, e.g. "12.345".
+fn format_time(at: Duration) -> String {
+    format!("{}.{:03}", at.as_secs(), at.subsec_millis())
+}
+
+/// A time in front of the bytes of a log line, `None` when there is none.
+fn parse_time(text: &str) -> Result<Option<Duration>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .map(Some)
+        .ok_or_else(|| format!("invalid time {:?}, expected seconds such as 12.345", text))
+}
+
+/// The read of a session log line: an optional time, then the first
+/// bracketed list of hex bytes, with or without `0x`. `None` for a blank
+/// line.
+pub fn parse_session_line(line: &str) -> Result<Option<Chunk>, String> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let start = line.find('[').ok_or_else(|| "no byte list, e.g. [01, 7F, 02]".to_string())?;
+    let end = line[start..]
+        .find(']')
+        .map(|end| start + end)
+        .ok_or_else(|| "unterminated byte list".to_string())?;
+
+    let at = parse_time(&line[..start])?;
+    let bytes = line[start + 1..end]
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let digits = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+            u8::from_str_radix(digits, 16).map_err(|_| format!("invalid hex byte {:?}", token))
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    Ok(Some(Chunk { at, bytes }))
+}
+
+/// The read of a hex log line: an optional time, then hex digits, ignoring
+/// whitespace. `None` for a blank line.
+pub fn parse_hex_line(line: &str) -> Result<Option<Chunk>, String> {
+    let line = line.trim();
+    // Hex digits hold no '.', so a first word with one is the time
+    let (at, hex) = match line.split_once(char::is_whitespace) {
+        Some((time, hex)) if time.contains('.') => (parse_time(time)?, hex),
+        _ => (None, line),
+    };
+
+    let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if digits.is_empty() {
+        return Ok(None);
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits: {}", digits.len()));
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).map_err(|_| "non-ASCII character".to_string())?;
+            u8::from_str_radix(pair, 16).map_err(|_| format!("invalid hex byte {:?}", pair))
+        })
+        .collect::<Result<Vec<u8>, String>>()
+        .map(|bytes| Some(Chunk { at, bytes }))
+}
+
+/// Read the reads stored in a log file.
+pub fn read_log(path: &Path, format: LogFormat) -> Result<Vec<Chunk>, LogError> {
+    let read_error = |error| LogError::Read {
+        path: path.to_path_buf(),
+        error,
+    };
+
+    if format == LogFormat::Raw {
+        let bytes = std::fs::read(path).map_err(read_error)?;
+        let chunks = bytes.chunks(RAW_CHUNK_LEN).map(|bytes| Chunk {
+            at: None,
+            bytes: bytes.to_vec(),
+        });
+        return Ok(chunks.collect());
+    }
+
+    let text = std::fs::read_to_string(path).map_err(read_error)?;
+    let parse_line = match format {
+        LogFormat::Hex => parse_hex_line,
+        _ => parse_session_line,
+    };
+    let mut chunks = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let chunk = parse_line(line).map_err(|message| LogError::InvalidLine {
+            path: path.to_path_buf(),
+            line: index + 1,
+            message,
+        })?;
+        chunks.extend(chunk);
+    }
+    Ok(chunks)
+}
+
+/// Write reads to `out` in a log format. Raw logs keep neither the read
+/// boundaries nor the times.
+pub fn write_log<W: Write>(out: &mut W, format: LogFormat, chunks: &[Chunk]) -> io::Result<()> {
+    for chunk in chunks {
+        match format {
+            LogFormat::Session => writeln!(out, "{}", format_line(chunk))?,
+            LogFormat::Hex => {
+                let hex: String = chunk.bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+                match chunk.at {
+                    Some(at) => writeln!(out, "{} {}", format_time(at), hex)?,
+                    None => writeln!(out, "{}", hex)?,
+                }
+            }
+            LogFormat::Raw => out.write_all(&chunk.bytes)?,
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(at: Option<Duration>, bytes: &[u8]) -> Chunk {
+        Chunk { at, bytes: bytes.to_vec() }
+    }
+
+    #[test]
+    fn timed_line_round_trip() {
+        let read = chunk(Some(Duration::from_millis(12_345)), &[0x01, 0x7F, 0x02, 0x47, 0x03, 0x24]);
+        let line = format_line(&read);
+        assert_eq!(line, "12.345 [01, 7F, 02, 47, 03, 24]");
+        assert_eq!(parse_session_line(&line), Ok(Some(read)));
+    }
+
+    #[test]
+    fn untimed_line_of_an_old_log() {
+        let read = chunk(None, &[0x01, 0x7F]);
+        assert_eq!(format_line(&read), "[01, 7F]");
+        assert_eq!(parse_session_line("[01, 7F]"), Ok(Some(read.clone())));
+        assert_eq!(parse_session_line("[0x01 0x7f]"), Ok(Some(read)));
+        assert_eq!(parse_session_line("   "), Ok(None));
+    }
+
+    #[test]
+    fn invalid_session_lines() {
+        for line in ["017F02", "1.5 [01, 7F", "[01, 7G]", "soon [01]", "-1.0 [01]"] {
+            assert!(parse_session_line(line).is_err(), "{:?} was accepted", line);
+        }
+    }
+
+    #[test]
+    fn every_format_round_trip() {
+        let reads = [
+            chunk(Some(Duration::ZERO), &[0x01, 0x7F, 0x02]),
+            chunk(Some(Duration::from_millis(1_500)), &[0x47, 0x03, 0x24]),
+        ];
+        for format in [LogFormat::Session, LogFormat::Hex] {
+            let mut out = Vec::new();
+            write_log(&mut out, format, &reads).unwrap();
+            let parse_line = if format == LogFormat::Hex { parse_hex_line } else { parse_session_line };
+            let parsed: Vec<Chunk> = String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .filter_map(|line| parse_line(line).unwrap())
+                .collect();
+            assert_eq!(parsed, reads, "{:?}", format);
+        }
+
+        let mut raw = Vec::new();
+        write_log(&mut raw, LogFormat::Raw, &reads).unwrap();
+        assert_eq!(raw, [0x01, 0x7F, 0x02, 0x47, 0x03, 0x24]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Made-up basketball games, sent the way a scorepad sends a real one, to
+//! rehearse overlays without a game on.
+//!
+//! Every second of the game clock gives a message 18 and a message 50, and
+//! a message 30 when someone scores. The clock counts down, in tenths in
+//! the last minute; the rest timer runs between periods; tied games go to
+//! overtime until someone wins. The same seed always plays the same game.
+
+use std::time::Duration;
+
+use crate::basketball_parser::{BodetMessage, Message18, Message30, Message50, StatusWord18, StatusWord50};
+use crate::frame::ProtocolFrame;
+
+/// Shot clock of a new possession.
+const SHOT_CLOCK_SECONDS: u8 = 24;
+
+/// Length of an overtime.
+const OVERTIME_MINUTES: u8 = 5;
+
+/// Rest between two quarters, or before an overtime.
+const BREAK_MINUTES: u8 = 2;
+
+/// Rest at halftime.
+const HALFTIME_MINUTES: u8 = 15;
+
+/// Settings of a simulated game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    pub periods: u8,        // regulation periods
+    pub period_minutes: u8, // game clock of a regulation period
+    pub seed: u64,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self {
+            periods: 4,
+            period_minutes: 10,
+            seed: 1,
+        }
+    }
+}
+
+impl Simulation {
+    /// Every frame of the game with the time it is sent at, from the start.
+    pub fn frames(&self) -> Vec<(Duration, ProtocolFrame)> {
+        let mut game = Game {
+            frames: Vec::new(),
+            elapsed: Duration::ZERO,
+            random: self.seed.max(1),
+            home_score: 0,
+            guest_score: 0,
+            shot_clock: SHOT_CLOCK_SECONDS,
+        };
+
+        game.send(BodetMessage::Message30(Message30::new(0, 0)));
+        let mut period = 1;
+        loop {
+            let overtime = period > self.periods;
+            let (period_byte, minutes) = if overtime {
+                (b'O', OVERTIME_MINUTES)
+            } else {
+                (b'0' + period, self.period_minutes)
+            };
+            game.play_period(period_byte, minutes, overtime && period > self.periods + 2);
+
+            if period >= self.periods && game.home_score != game.guest_score {
+                break;
+            }
+            let rest = if !overtime && period * 2 == self.periods { HALFTIME_MINUTES } else { BREAK_MINUTES };
+            game.rest(period_byte, rest);
+            period += 1;
+        }
+        game.frames
+    }
+}
+
+/// A game being played.
+struct Game {
+    frames: Vec<(Duration, ProtocolFrame)>,
+    elapsed: Duration,
+    random: u64, // xorshift state
+    home_score: u16,
+    guest_score: u16,
+    shot_clock: u8,
+}
+
+impl Game {
+    fn send(&mut self, message: BodetMessage) {
+        self.frames.push((self.elapsed, message.to_frame()));
+    }
+
+    /// A random number below `bound`.
+    fn random(&mut self, bound: u64) -> u64 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        self.random % bound
+    }
+
+    /// Play a period, one second at a time. A `decisive` period can't end
+    /// tied, so that overtimes come to an end.
+    fn play_period(&mut self, period: u8, minutes: u8, decisive: bool) {
+        let running = StatusWord18 { b7: true, ..StatusWord18::default() };
+        let shot_clock_running = StatusWord50 { b7: true, ..StatusWord50::default() };
+        self.shot_clock = SHOT_CLOCK_SECONDS;
+
+        for remaining in (1..=minutes as u16 * 60).rev() {
+            let clock = if remaining < 60 {
+                Message18::with_tenths(running, remaining as u8, 0, 0, 0, period)
+            } else {
+                Message18::new(running, (remaining / 60) as u8, (remaining % 60) as u8, 0, 0, period)
+            };
+            self.send(BodetMessage::Message18(clock));
+            self.send(BodetMessage::Message50(Message50::new(shot_clock_running, self.shot_clock)));
+
+            let tied_at_the_end = decisive && remaining == 1 && self.home_score == self.guest_score;
+            if self.random(12) == 0 || tied_at_the_end {
+                let points = [2, 2, 2, 3, 1][self.random(5) as usize];
+                if self.random(2) == 0 {
+                    self.home_score += points;
+                } else {
+                    self.guest_score += points;
+                }
+                self.send(BodetMessage::Message30(Message30::new(self.home_score, self.guest_score)));
+                self.shot_clock = SHOT_CLOCK_SECONDS;
+            } else {
+                // A missed shot or a turnover resets the shot clock too
+                self.shot_clock = self.shot_clock.saturating_sub(1);
+                if self.shot_clock == 0 {
+                    self.shot_clock = SHOT_CLOCK_SECONDS;
+                }
+            }
+            self.elapsed += Duration::from_secs(1);
+        }
+
+        // The clock runs out with the horn, then stops
+        let horn = StatusWord18 { horn_on: true, ..running };
+        let stopped = StatusWord18 { game_clock_off: true, ..running };
+        self.send(BodetMessage::Message18(Message18::with_tenths(horn, 0, 0, 0, 0, period)));
+        self.elapsed += Duration::from_secs(1);
+        self.send(BodetMessage::Message18(Message18::with_tenths(stopped, 0, 0, 0, 0, period)));
+        self.elapsed += Duration::from_secs(1);
+    }
+
+    /// Run the rest timer after `period`.
+    fn rest(&mut self, period: u8, minutes: u8) {
+        let rest = StatusWord18 { clock_type: true, b7: true, ..StatusWord18::default() };
+        for remaining in (1..=minutes as u16 * 60).rev() {
+            let clock = Message18::new(rest, (remaining / 60) as u8, (remaining % 60) as u8, 0, 0, period);
+            self.send(BodetMessage::Message18(clock));
+            self.elapsed += Duration::from_secs(1);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Sport-aware decoding: picks the message layout of the sport a frame
+//! belongs to.
+//!
+//! Message types are unique to a sport (basketball uses 18-60, handball
+//! 01-04, volleyball 06-08, futsal 45), so the type selects the decoder and
+//! the sports id byte is then checked against it. Basketball and basketball
+//! 3x3 share their message types and only differ by the sports id. Team
+//! names (98/99) are the same for every sport.
+
+use serde::{Deserialize, Serialize};
+
+use crate::basketball_parser::{BodetMessage, SPORTS_ID_BASKETBALL, SPORTS_ID_BASKETBALL_3X3};
+use crate::error::ParseError;
+use crate::frame::{Control, ProtocolFrame};
+use crate::futsal_parser::FutsalMessage;
+use crate::handball_parser::HandballMessage;
+use crate::volleyball_parser::VolleyballMessage;
+
+/// Sports supported by the decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sport {
+    Basketball,
+    #[serde(rename = "basketball3x3")]
+    Basketball3x3,
+    Handball,
+    Futsal,
+    Volleyball,
+}
+
+impl Sport {
+    /// Sport of a basketball sports id byte ('5' or '8').
+    pub fn from_basketball_sports_id(sports_id: u8) -> Option<Self> {
+        match sports_id {
+            SPORTS_ID_BASKETBALL => Some(Sport::Basketball),
+            SPORTS_ID_BASKETBALL_3X3 => Some(Sport::Basketball3x3),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded message of any supported sport.
+///
+/// Decode a frame with `SportMessage::try_from(&frame)`; apply it to a
+/// GameState with `GameState::apply_message`. Frames whose CTRL byte is not
+/// 'G' are refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SportMessage {
+    /// Basketball and basketball 3x3 messages, and the team names.
+    Basketball(BodetMessage),
+    Handball(HandballMessage),
+    Futsal(FutsalMessage),
+    Volleyball(VolleyballMessage),
+}
+
+impl SportMessage {
+    /// Sport the message belongs to, `None` for messages shared by every
+    /// sport or that don't carry a sports id.
+    pub fn sport(&self) -> Option<Sport> {
+        match self {
+            SportMessage::Basketball(message) => message.sport(),
+            SportMessage::Handball(_) => Some(Sport::Handball),
+            SportMessage::Futsal(_) => Some(Sport::Futsal),
+            SportMessage::Volleyball(_) => Some(Sport::Volleyball),
+        }
+    }
+}
+
+impl From<BodetMessage> for SportMessage {
+    fn from(message: BodetMessage) -> Self {
+        SportMessage::Basketball(message)
+    }
+}
+
+impl TryFrom<&ProtocolFrame> for SportMessage {
+    type Error = ParseError;
+
+    fn try_from(frame: &ProtocolFrame) -> Result<Self, Self::Error> {
+        if let Control::Other(ctrl) = frame.control() {
+            return Err(ParseError::UnexpectedControl(ctrl));
+        }
+
+        match frame.message.get(..2) {
+            Some([0x30, 0x31..=0x34]) => HandballMessage::try_from(frame).map(SportMessage::Handball),
+            Some([0x30, 0x36..=0x38]) => VolleyballMessage::try_from(frame).map(SportMessage::Volleyball),
+            Some([0x34, 0x35]) => FutsalMessage::try_from(frame).map(SportMessage::Futsal),
+            _ => BodetMessage::try_from(frame).map(SportMessage::Basketball),
+        }
+    }
+}
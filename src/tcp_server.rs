@@ -0,0 +1,223 @@
+//! TCP ingest: accepts scorepad connections and feeds their frames into the
+//! GameState of their court.
+
+use log::{error, info, warn};
+use std::{
+    fs::OpenOptions, io::{Read, Write}, net::{IpAddr, SocketAddr, TcpListener, TcpStream}, path::{Path, PathBuf}, sync::Arc, thread, time::{Duration, Instant, SystemTime, UNIX_EPOCH}
+};
+
+use crate::court::{Court, Courts};
+use crate::frame::{FrameDecoder, ProtocolFrame};
+use crate::game_state::GameArchive;
+use crate::session_log::{self, Chunk};
+use crate::web_server::SseMessage;
+
+/// Accept scorepad connections on `address`, handling each one in its own
+/// thread. Only returns if the listener cannot be bound.
+///
+/// Sessions and finished games are logged to `log_dir`, or not at all
+/// when it is `None`.
+pub fn run(address: SocketAddr, log_dir: Option<PathBuf>, courts: Arc<Courts>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    info!("Basketball Protocol Server listening on {}", address);
+    info!("Waiting for connections...");
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let courts = Arc::clone(&courts);
+                let log_dir = log_dir.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_client(stream, log_dir.as_deref(), courts) {
+                        error!("Error handling client: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                error!("Error accepting connection: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply a frame to the state of a court and push the new state and any
+/// events to its SSE clients. Returns the finished game when the frame
+/// started a new match.
+///
+/// The first frame with an unexpected CTRL byte is also pushed, as a
+/// `diagnostic` event carrying the court diagnostics.
+pub fn process_frame(frame: &ProtocolFrame, court: &Court) -> Option<GameArchive> {
+    let (update, snapshot) = {
+        let mut state = court.game_state.lock().unwrap();
+        let update = state.apply_frame(frame);
+        let snapshot = if update.changed { Some(state.clone()) } else { None };
+        (update, snapshot)
+    };
+
+    let new_control_code = {
+        let mut diagnostics = court.diagnostics.lock().unwrap();
+        diagnostics
+            .record(frame, update.error.as_ref())
+            .then(|| serde_json::to_string(&*diagnostics))
+    };
+    if let Some(Ok(data)) = new_control_code {
+        warn!("Court {:?} received CTRL byte 0x{:02X}, not decoded", court.id, frame.ctrl);
+        let _ = court.broadcast_tx.send(SseMessage::Event { name: "diagnostic", data });
+    }
+
+    if let Some(snapshot) = snapshot {
+        let _ = court.broadcast_tx.send(SseMessage::State(Arc::new(snapshot)));
+    }
+
+    for event in update.events {
+        if let Ok(data) = serde_json::to_string(&event) {
+            let _ = court.broadcast_tx.send(SseMessage::Event { name: event.name(), data });
+        }
+    }
+
+    update.finished_game
+}
+
+/// Feed the bytes of one read from `peer` to `decoder` and process every
+/// frame it completes, each in the court it belongs to. Returns the games
+/// finished by these frames, with their court.
+pub fn process_read(decoder: &mut FrameDecoder, bytes: &[u8], courts: &Courts, peer: IpAddr) -> Vec<(Arc<Court>, GameArchive)> {
+    let mut finished_games = Vec::new();
+    decoder.push(bytes);
+    while let Some(result) = decoder.next_frame() {
+        match result {
+            Ok(frame) => {
+                let Some(court) = courts.court(&courts.court_id(&frame, peer)) else {
+                    continue;
+                };
+                if let Some(archive) = process_frame(&frame, &court) {
+                    finished_games.push((court, archive));
+                }
+            }
+            Err(e) => {
+                warn!("Failed to parse ProtocolFrame from {}: {}", peer, e);
+            }
+        }
+    }
+    finished_games
+}
+
+/// `<seconds>.<millis>` since the epoch, used to name log files.
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}.{}", now.as_secs(), now.subsec_millis())
+}
+
+/// Write a finished game to `<log_dir>/game-<court>-<timestamp>.json`.
+fn archive_game(archive: &GameArchive, court_id: &str, log_dir: &Path) {
+    if let Err(e) = std::fs::create_dir_all(log_dir) {
+        error!("Failed to create log directory {}: {}", log_dir.display(), e);
+    }
+
+    // IPv6 court ids hold colons
+    let file_name = log_dir.join(format!("game-{}-{}.json", court_id.replace(':', "_"), timestamp()));
+    let result = serde_json::to_vec_pretty(archive)
+        .map_err(std::io::Error::from)
+        .and_then(|json| std::fs::write(&file_name, json));
+    match result {
+        Ok(()) => info!("Archived finished game to {}", file_name.display()),
+        Err(e) => error!("Failed to archive finished game to {}: {}", file_name.display(), e),
+    }
+}
+
+/// Handle a single scorepad connection until it closes, routing each frame
+/// to its court.
+///
+/// When `log_dir` is set, every read is appended to a new
+/// `<log_dir>/session-<timestamp>.log` file as a hex byte list, after its
+/// time from the start of the connection.
+pub fn handle_client(mut stream: TcpStream, log_dir: Option<&Path>, courts: Arc<Courts>) -> std::io::Result<()> {
+    let peer_addr = stream.peer_addr()?;
+    info!("New connection from: {}", peer_addr);
+
+    // Set read timeout to prevent hanging
+    stream.set_read_timeout(Some(Duration::from_secs(300)))?;
+
+    // Create the log directory and open a new per-session file named with a timestamp
+    // only if file logging is enabled. Do not write a header — raw bytes only.
+    // Failures to create/open the file are logged and do not terminate the client connection.
+    let mut log_file: Option<std::fs::File> = if let Some(log_dir) = log_dir {
+        // ensure directory exists
+        if let Err(e) = std::fs::create_dir_all(log_dir) {
+            error!("Failed to create log directory {}: {}", log_dir.display(), e);
+        }
+
+        // timestamp-based filename (seconds + millis to reduce collisions)
+        let file_name = log_dir.join(format!("session-{}.log", timestamp()));
+
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_name)
+        {
+            Ok(f) => {
+                info!("Logging TCP session to {}", file_name.display());
+                Some(f)
+            }
+            Err(e) => {
+                error!("Failed to open session log file {}: {}", file_name.display(), e);
+                None
+            }
+        }
+    } else {
+        info!("Session file logging is disabled for this run");
+        None
+    };
+
+    let mut buffer = [0u8; 1024];
+    let mut decoder = FrameDecoder::new();
+    let session_start = Instant::now();
+
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => {
+                // Connection closed
+                info!("Connection closed by: {}", peer_addr);
+                break;
+            }
+            Ok(n) => {
+                // Write each TCP read as a single newline-delimited line containing
+                // its time and a hex-style byte array (matching the debug output), e.g.:
+                // 12.345 [01, 7F, 02, ...]
+                if let Some(ref mut f) = log_file {
+                    let line = session_log::format_line(&Chunk {
+                        at: Some(session_start.elapsed()),
+                        bytes: buffer[..n].to_vec(),
+                    });
+                    if let Err(e) = writeln!(f, "{}", line) {
+                        warn!("Failed to write raw bytes to log file: {}", e);
+                    }
+                    // best-effort flush to ensure data is on-disk quickly
+                    if let Err(e) = f.flush() {
+                        warn!("Failed to flush log file: {}", e);
+                    }
+                }
+
+                // Feed the received bytes to the decoder and handle every
+                // complete frame it has reassembled so far
+                let finished_games = process_read(&mut decoder, &buffer[..n], &courts, peer_addr.ip());
+                // Finished games are archived next to the session logs
+                if let Some(log_dir) = log_dir {
+                    for (court, archive) in finished_games {
+                        archive_game(&archive, &court.id, log_dir);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Error reading from {}: {}", peer_addr, e);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
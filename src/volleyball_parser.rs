@@ -0,0 +1,191 @@
+//! Volleyball messages of the Bodet Scorepad protocol.
+//!
+//! Same conventions as `basketball_parser`: each `MessageNN` struct mirrors
+//! the byte layout of message type NN and holds the raw ASCII bytes, and
+//! `GameState::apply_volleyball` updates the state from it.
+
+use crate::basketball_parser::{check_digits, check_len, check_type_len, check_sports_id};
+use crate::error::ParseError;
+use crate::frame::ProtocolFrame;
+
+/// Sports id byte of volleyball messages.
+pub const SPORTS_ID_VOLLEYBALL: u8 = b'0';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message06 {
+    pub id_1: u8,              // First byte of message ID
+    pub id_2: u8,              // Second byte of message ID
+    pub status_word: u8,       // Status word byte
+    pub sports_id: u8,         // This needs to be 0 for volleyball
+    pub set: u8,               // Current set number
+    pub home_sets: u8,         // Sets won by home
+    pub guest_sets: u8,        // Sets won by guest
+    pub home_score_1: u8,      // Home score of the current set * 10
+    pub home_score_2: u8,      // Home score of the current set * 1
+    pub guest_score_1: u8,     // Guest score of the current set * 10
+    pub guest_score_2: u8,     // Guest score of the current set * 1
+    pub home_time_outs: u8,    // Home time-outs
+    pub guest_time_outs: u8,   // Guest time-outs
+    pub seconds_1: u8,         // Time-out countdown seconds * 10
+    pub seconds_2: u8,         // Time-out countdown seconds * 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message07 {
+    pub id_1: u8,              // First byte of message ID
+    pub id_2: u8,              // Second byte of message ID
+    pub status_word: u8,       // Status word byte
+    pub sports_id: u8,         // This needs to be 0 for volleyball
+    pub minutes_1: u8,         // Minutes * 10
+    pub minutes_2: u8,         // Minutes * 1
+    pub seconds_1: u8,         // Seconds * 10
+    pub seconds_2: u8,         // Seconds * 1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message08 {
+    pub id_1: u8,              // First byte of message ID
+    pub id_2: u8,              // Second byte of message ID
+    pub status_word: u8,       // Status word byte
+    pub sports_id: u8,         // This needs to be 0 for volleyball
+    pub set_scores: Vec<u8>,   // Scores of the ended sets, home and guest on 2 characters each
+}
+
+/// Status word of volleyball messages 06-08.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatusWordVolleyball {
+    pub game_clock_off: bool, // bit 1
+    pub horn_on: bool,        // bit 2
+    pub home_serving: bool,   // bit 3, guest serving when clear
+    pub b7: bool,             // bit 7
+}
+
+impl StatusWordVolleyball {
+    pub fn from_byte(byte: u8) -> Self {
+        Self {
+            game_clock_off: (byte & (1 << 1)) != 0,
+            horn_on: (byte & (1 << 2)) != 0,
+            home_serving: (byte & (1 << 3)) != 0,
+            b7: (byte & (1 << 7)) != 0,
+        }
+    }
+}
+
+/// A decoded volleyball message, one variant per message type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VolleyballMessage {
+    /// Message 06: set, sets won, score of the current set and time-outs.
+    Message06(Message06),
+    /// Message 07: game clock.
+    Message07(Message07),
+    /// Message 08: scores of the ended sets.
+    Message08(Message08),
+}
+
+impl TryFrom<&ProtocolFrame> for VolleyballMessage {
+    type Error = ParseError;
+
+    fn try_from(frame: &ProtocolFrame) -> Result<Self, Self::Error> {
+        let message = &frame.message;
+
+        check_type_len(message)?;
+
+        match (message[0], message[1]) {
+            // Message Type 06
+            (0x30, 0x36) => {
+                check_len(6, message, 15)?;
+
+                let decoded = Message06 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    sports_id: message[3],
+                    set: message[4],
+                    home_sets: message[5],
+                    guest_sets: message[6],
+                    home_score_1: message[7],
+                    home_score_2: message[8],
+                    guest_score_1: message[9],
+                    guest_score_2: message[10],
+                    home_time_outs: message[11],
+                    guest_time_outs: message[12],
+                    seconds_1: message[13],
+                    seconds_2: message[14],
+                };
+
+                check_sports_id(6, decoded.sports_id, &[SPORTS_ID_VOLLEYBALL])?;
+                check_digits(
+                    6,
+                    &[
+                        ("set", decoded.set),
+                        ("home_sets", decoded.home_sets),
+                        ("guest_sets", decoded.guest_sets),
+                        ("home_score_1", decoded.home_score_1),
+                        ("home_score_2", decoded.home_score_2),
+                        ("guest_score_1", decoded.guest_score_1),
+                        ("guest_score_2", decoded.guest_score_2),
+                        ("home_time_outs", decoded.home_time_outs),
+                        ("guest_time_outs", decoded.guest_time_outs),
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                    ],
+                )?;
+
+                Ok(VolleyballMessage::Message06(decoded))
+            }
+
+            // Message Type 07
+            (0x30, 0x37) => {
+                check_len(7, message, 8)?;
+
+                let decoded = Message07 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    sports_id: message[3],
+                    minutes_1: message[4],
+                    minutes_2: message[5],
+                    seconds_1: message[6],
+                    seconds_2: message[7],
+                };
+
+                check_sports_id(7, decoded.sports_id, &[SPORTS_ID_VOLLEYBALL])?;
+                check_digits(
+                    7,
+                    &[
+                        ("minutes_1", decoded.minutes_1),
+                        ("minutes_2", decoded.minutes_2),
+                        ("seconds_1", decoded.seconds_1),
+                        ("seconds_2", decoded.seconds_2),
+                    ],
+                )?;
+
+                Ok(VolleyballMessage::Message07(decoded))
+            }
+
+            // Message Type 08
+            (0x30, 0x38) => {
+                check_len(8, message, 8)?;
+
+                // Sets are 4 bytes each; ignore a trailing partial set
+                let sets_end = 4 + (message.len() - 4) / 4 * 4;
+                let decoded = Message08 {
+                    id_1: message[0],
+                    id_2: message[1],
+                    status_word: message[2],
+                    sports_id: message[3],
+                    set_scores: message[4..sets_end].to_vec(),
+                };
+
+                check_sports_id(8, decoded.sports_id, &[SPORTS_ID_VOLLEYBALL])?;
+                for &byte in &decoded.set_scores {
+                    check_digits(8, &[("set_scores", byte)])?;
+                }
+
+                Ok(VolleyballMessage::Message08(decoded))
+            }
+
+            _ => Err(ParseError::UnknownMessageType(message[0], message[1])),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Web server: the overlay's static files, the JSON API and the SSE streams
+//! of every court.
+
+use log::{debug, info};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use warp::Filter;
+
+use crate::court::{Court, Courts};
+use crate::game_state::GameState;
+
+/// What is pushed to SSE clients: a full game state snapshot, serialized
+/// per client in the API version it asked for, or a named event (e.g.
+/// "fouled_out") with its JSON payload.
+#[derive(Debug, Clone)]
+pub enum SseMessage {
+    State(Arc<GameState>),
+    Event { name: &'static str, data: String },
+}
+
+/// Version of the game state JSON, picked by the path of the request:
+/// `/api/game` and `/api/v1/game` serve version 1, `/api/v2/game` version 2
+/// (same for `stream`).
+///
+/// Version 1 is what the overlay was written against: scores, clock, period,
+/// fouls, time-outs and the shot clock are display strings at the top level.
+/// Version 2 has them as numbers, with the display strings under `display`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub fn number(self) -> u8 {
+        match self {
+            ApiVersion::V1 => 1,
+            ApiVersion::V2 => 2,
+        }
+    }
+
+    /// Serialize a game state in this version, with a `version` field.
+    pub fn to_json(self, state: &GameState) -> serde_json::Result<String> {
+        let mut json = serde_json::to_value(state)?;
+        if let serde_json::Value::Object(fields) = &mut json {
+            if self == ApiVersion::V1 {
+                // The display strings take the place of the typed values
+                if let Some(serde_json::Value::Object(display)) = fields.remove("display") {
+                    fields.extend(display);
+                }
+            }
+            fields.insert("version".to_string(), self.number().into());
+        }
+        serde_json::to_string(&json)
+    }
+}
+
+/// Reply with the game state of a court in an API version.
+fn game_reply(version: ApiVersion, court: Arc<Court>) -> impl warp::Reply {
+    let json = version.to_json(&court.game_state.lock().unwrap()).unwrap_or_default();
+    warp::reply::with_header(json, "content-type", "application/json")
+}
+
+/// Stream the game state and events of a court to an SSE client.
+fn stream_reply(version: ApiVersion, court: Arc<Court>) -> impl warp::Reply {
+    let mut rx = court.broadcast_tx.subscribe();
+    let state = Arc::clone(&court.game_state);
+    let court_id = court.id.clone();
+
+    let stream = async_stream::stream! {
+        // Send current state immediately, so a freshly opened overlay
+        // doesn't wait for the next update (team names are sent rarely)
+        {
+            let snapshot = state.lock().unwrap().clone();
+            let current_state = version.to_json(&snapshot).unwrap_or_default();
+            yield Ok::<_, std::convert::Infallible>(warp::sse::Event::default().data(current_state));
+        }
+
+        loop {
+            let message = match rx.recv().await {
+                Ok(message) => message,
+                // A client that fell behind, e.g. during an accelerated
+                // replay, skips to the next state, which holds everything
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("SSE client of court {:?} skipped {} messages", court_id, skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let event = match message {
+                SseMessage::State(state) => warp::sse::Event::default().data(version.to_json(&state).unwrap_or_default()),
+                SseMessage::Event { name, data } => warp::sse::Event::default().event(name).data(data),
+            };
+            yield Ok::<_, std::convert::Infallible>(event);
+        }
+    };
+
+    warp::sse::reply(warp::sse::keep_alive().stream(stream))
+}
+
+/// Serve the overlay from `static_dir`, the game state of every court and
+/// their SSE streams on `address`. `/api/game` and `/api/stream` serve the
+/// default court. Only returns if `address` cannot be bound.
+pub async fn start_web_server(courts: Arc<Courts>, address: SocketAddr, static_dir: PathBuf) -> Result<(), warp::Error> {
+    // GET / -> serve overlay.html
+    let index = warp::path::end()
+        .and(warp::fs::file(static_dir.join("overlay.html")));
+
+    // GET /overlay.css -> serve overlay.css
+    let css = warp::path("overlay.css")
+        .and(warp::fs::file(static_dir.join("overlay.css")));
+
+    // GET /overlay.js -> serve overlay.js
+    let js = warp::path("overlay.js")
+        .and(warp::fs::file(static_dir.join("overlay.js")));
+
+    // GET /media/* -> serve assets from static/media
+    let media = warp::path("media")
+        .and(warp::fs::dir(static_dir.join("media")));
+
+    // /api/..., /api/v1/... or /api/v2/... -> API version
+    let api_version = warp::path("api").and(
+        warp::path("v1")
+            .map(|| ApiVersion::V1)
+            .or(warp::path("v2").map(|| ApiVersion::V2))
+            .unify()
+            .or(warp::any().map(|| ApiVersion::V1))
+            .unify(),
+    );
+
+    // The default court, not found until a scorepad connects when courts
+    // are routed by connection
+    let default_courts = Arc::clone(&courts);
+    let default_court = warp::any().and_then(move || {
+        let court = default_courts.default_court();
+        async move { court.ok_or_else(warp::reject::not_found) }
+    });
+
+    // courts/<id> -> that court
+    let id_courts = Arc::clone(&courts);
+    let court_by_id = warp::path("courts").and(warp::path::param()).and_then(move |id: String| {
+        let court = id_courts.get(&id);
+        async move { court.ok_or_else(warp::reject::not_found) }
+    });
+
+    // GET /api/game, /api/courts/<id>/game -> return current game state
+    let game_api = api_version
+        .and(default_court.clone().and(warp::path!("game")).or(court_by_id.clone().and(warp::path!("game"))).unify())
+        .map(game_reply);
+
+    // GET /api/stream, /api/courts/<id>/stream -> SSE endpoint
+    let stream_api = api_version
+        .and(default_court.clone().and(warp::path!("stream")).or(court_by_id.clone().and(warp::path!("stream"))).unify())
+        .map(stream_reply);
+
+    // GET /api/diagnostics, /api/courts/<id>/diagnostics -> frames received
+    // besides game messages
+    let diagnostics_api = warp::path("api")
+        .and(default_court.and(warp::path!("diagnostics")).or(court_by_id.and(warp::path!("diagnostics"))).unify())
+        .map(|court: Arc<Court>| warp::reply::json(&*court.diagnostics.lock().unwrap()));
+
+    // GET /api/courts -> ids of every court
+    let courts_api = warp::path!("api" / "courts").map(move || warp::reply::json(&courts.ids()));
+
+    let routes = index
+        .or(css)
+        .or(js)
+        .or(media)
+        .or(courts_api)
+        .or(diagnostics_api)
+        .or(game_api)
+        .or(stream_api);
+
+    let (address, server) = warp::serve(routes).try_bind_ephemeral(address)?;
+    info!("Web server listening on http://{}, static files from {}", address, static_dir.display());
+    server.await;
+    Ok(())
+}
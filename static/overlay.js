@@ -2,15 +2,25 @@
 let currentGameState = null;
 
 // Get URL parameters
+// Team names given in the URL override the names typed on the scorepad
 const urlParams = new URLSearchParams(window.location.search);
-const homeTeamName = urlParams.get('home') || 'HOME';
-const awayTeamName = urlParams.get('away') || 'AWAY';
+const homeTeamName = urlParams.get('home');
+const awayTeamName = urlParams.get('away');
 const homeLogo = urlParams.get('homeLogo') || '';
 const awayLogo = urlParams.get('awayLogo') || '';
+// Court to show when the server drives several, e.g. ?court=127
+const court = urlParams.get('court');
+const apiBase = court ? `/api/courts/${encodeURIComponent(court)}` : '/api';
 
 // Update team names
-document.getElementById('home-team-name').textContent = homeTeamName;
-document.getElementById('away-team-name').textContent = awayTeamName;
+function updateTeamName(team, urlName, scorepadName, fallback) {
+    const element = document.getElementById(`${team}-team-name`);
+    if (!element) return;
+
+    element.textContent = urlName || scorepadName || fallback;
+}
+updateTeamName('home', homeTeamName, null, 'HOME');
+updateTeamName('away', awayTeamName, null, 'AWAY');
 
 // Update logos
 function setLogo(team, logoUrl) {
@@ -33,7 +43,7 @@ setLogo('home', homeLogo);
 setLogo('away', awayLogo);
 
 // Connect to SSE endpoint
-const evtSource = new EventSource('/api/stream');
+const evtSource = new EventSource(`${apiBase}/stream`);
 
 evtSource.onopen = () => {
     // Connection opened
@@ -53,6 +63,10 @@ evtSource.onmessage = (event) => {
 };
 
 function updateScoreboard(data) {
+    // Update team names
+    updateTeamName('home', homeTeamName, data.home_name, 'HOME');
+    updateTeamName('away', awayTeamName, data.away_name, 'AWAY');
+
     // Update scores with animation only if changed
     updateElementIfChanged('home-score', data.home_score);
     updateElementIfChanged('away-score', data.away_score);
@@ -72,7 +86,7 @@ function updateScoreboard(data) {
     updateTimeouts('away', data.away_timeouts);
     
     // Update shot clock
-    updateShotClock(data.shot_clock);
+    updateShotClock(data.shot_clock, data.shot_clock_state);
     
     // Update game state (pause dot)
     updateGameState(data.game_state);
@@ -145,10 +159,14 @@ function updateTime(timeStr) {
     }
 }
 
-function updateShotClock(shotClock) {
+function updateShotClock(shotClock, shotClockState) {
     const element = document.getElementById('shot-clock');
     if (!element) return;
     
+    // Hide the shot clock when the console blanks it, dim it while it's stopped
+    element.classList.toggle('blank', shotClockState === 'blank');
+    element.classList.toggle('stopped', shotClockState === 'stopped');
+    
     const newValue = shotClock || '--';
     
     if (element.textContent !== newValue) {
@@ -170,7 +188,7 @@ function updateGameState(gameState) {
 }
 
 // Initial fetch to get current state
-fetch('/api/game')
+fetch(`${apiBase}/game`)
     .then(response => response.json())
     .then(data => {
         if (data) {